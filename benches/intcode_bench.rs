@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+#[path = "../src/common.rs"]
+#[allow(dead_code)]
+mod common;
+#[path = "../src/intcode/mod.rs"]
+#[allow(dead_code)]
+mod intcode;
+
+use intcode::assembler::assemble;
+use intcode::{IntcodeComputer, IntcodeReturn};
+
+// Sums 3 * i for i from 100000 down to 1, around 400000 steps
+const SUM_LOOP: &str = "
+loop:
+    mul [counter], #3, [tmp]
+    add [total], [tmp], [total]
+    add [counter], #-1, [counter]
+    jt [counter], #loop
+    out [total]
+    hlt
+counter: data 100000
+total: data 0
+tmp: data 0
+";
+
+fn run(program: &[i128], precompile: bool) -> i128 {
+    let mut computer = IntcodeComputer::new(program.to_vec());
+    if precompile {
+        computer.precompile();
+    }
+    match computer.run() {
+        Ok(IntcodeReturn::Output(value)) => value,
+        _ => panic!("Benchmark program didn't output its total"),
+    }
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let program = assemble(SUM_LOOP).unwrap();
+    c.bench_function("intcode_interpreted", |b| {
+        b.iter(|| run(black_box(&program), false))
+    });
+    c.bench_function("intcode_precompiled", |b| {
+        b.iter(|| run(black_box(&program), true))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+#[path = "../common.rs"]
+#[allow(dead_code)]
+mod common;
+#[path = "../intcode/mod.rs"]
+#[allow(dead_code)]
+mod intcode;
+
+use intcode::ascii::AsciiComputer;
+use intcode::{load_program, IntcodeComputer};
+use std::env;
+use std::fs;
+
+const USAGE: &str = "Usage: intcode_ascii <program file> [--script FILE]";
+
+// Plays a text-driven program in the terminal. Lines of the script, if any, are
+// queued as input before handing over to the keyboard.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (filepath, script) = match args.as_slice() {
+        [filepath] => (filepath, None),
+        [filepath, option, script] if option == "--script" => (filepath, Some(script)),
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+
+    let mut computer = AsciiComputer::new(IntcodeComputer::new(load_program(filepath)));
+    if let Some(script) = script {
+        match fs::read_to_string(script) {
+            Ok(text) => {
+                for line in text.lines() {
+                    computer.push_line(line);
+                }
+            }
+            Err(error) => {
+                println!("Can't read script {}: {}", script, error);
+                return;
+            }
+        }
+    }
+    if let Err(error) = computer.interactive() {
+        println!("Error: {}", error);
+    }
+}
@@ -0,0 +1,208 @@
+#[path = "../common.rs"]
+#[allow(dead_code)]
+mod common;
+#[path = "../intcode/mod.rs"]
+#[allow(dead_code)]
+mod intcode;
+
+use intcode::debugger::{Debugger, Resume, Stop};
+use intcode::disassembler::disassemble;
+use intcode::load_program;
+use std::env;
+use std::io::{stdin, stdout, Write};
+use std::ops::Range;
+
+const HELP: &str = "Commands:
+  s, step [n]          execute n instructions (default 1)
+  c, continue          run until a breakpoint, watchpoint, input wait or halt
+  f, finish            run until input wait or halt, ignoring breakpoints
+  b, break <addr>      toggle a breakpoint on an address
+  bo <opcode>          toggle a breakpoint on an opcode
+  w, watch <addr>      toggle a watchpoint on a memory cell
+  i, input <values>    queue input values
+  x <addr> [count]     dump memory
+  poke <addr> <value>  write a value into memory
+  l, list [addr] [n]   disassemble n instructions (default 10 from the pointer)
+  r, regs              show pointer and relative base
+  info                 list breakpoints and watchpoints
+  smc                  list writes into instructions already executed
+  q, quit              exit";
+
+fn print_registers(debugger: &Debugger) {
+    let computer = debugger.computer();
+    println!("ip={} rb={}", computer.pointer(), computer.relative_base());
+}
+
+// Most cells or instructions a single x or list command shows
+const MAX_COUNT: usize = 10_000;
+
+// Cells covering count items of width cells each from an address, if that stays in range
+fn cell_range(from: usize, count: usize, width: usize) -> Option<Range<usize>> {
+    if count > MAX_COUNT {
+        return None;
+    }
+    Some(from..from.checked_add(count.checked_mul(width)?)?)
+}
+
+fn print_listing(debugger: &Debugger, from: usize, count: usize) {
+    let computer = debugger.computer();
+    let range = match cell_range(from, count, 4) {
+        Some(range) => range,
+        None => {
+            println!("Usage: l [addr] [n], at most {} instructions", MAX_COUNT);
+            return;
+        }
+    };
+    let image = computer.memory_range(range);
+    for mut line in disassemble(&image).into_iter().take(count) {
+        line.address += from;
+        let marker = if line.address == computer.pointer() {
+            ">"
+        } else {
+            " "
+        };
+        println!("{}{}", marker, line);
+    }
+}
+
+fn print_stop(debugger: &mut Debugger, stop: Stop) {
+    for output in debugger.take_outputs() {
+        println!("Output: {}", output);
+    }
+    match stop {
+        Stop::Stepped => {}
+        Stop::Breakpoint(address) => println!("Breakpoint at {}", address),
+        Stop::OpcodeBreakpoint(opcode) => println!("Breakpoint on opcode {}", opcode),
+        Stop::Watchpoint { address, old, new } => {
+            println!("Watchpoint [{}]: {} -> {}", address, old, new)
+        }
+        Stop::WaitForInput => println!("Waiting for input, queue some with 'input'"),
+        Stop::End => println!("Program halted"),
+        Stop::Error(error) => println!("Error: {}", error),
+    }
+    let pointer = debugger.computer().pointer();
+    print_listing(debugger, pointer, 1);
+}
+
+fn parse<T: std::str::FromStr>(argument: Option<&&str>) -> Option<T> {
+    argument.and_then(|a| a.parse::<T>().ok())
+}
+
+fn main() {
+    let filepath = match env::args().nth(1) {
+        Some(filepath) => filepath,
+        None => {
+            println!("Usage: intcode_debugger <program file>");
+            return;
+        }
+    };
+    let mut debugger = Debugger::new(load_program(&filepath));
+    println!("{}", HELP);
+    print_listing(&debugger, 0, 1);
+
+    loop {
+        print!("(icdb) ");
+        stdout().flush().unwrap();
+        let mut buffer = String::new();
+        if stdin().read_line(&mut buffer).unwrap() == 0 {
+            break;
+        }
+        let words: Vec<&str> = buffer.split_whitespace().collect();
+        let command = match words.first() {
+            Some(command) => *command,
+            None => continue,
+        };
+
+        match command {
+            "s" | "step" => {
+                let count = parse::<usize>(words.get(1)).unwrap_or(1);
+                let mut stop = Stop::Stepped;
+                for _ in 0..count {
+                    stop = debugger.resume(Resume::Step);
+                    if stop != Stop::Stepped {
+                        break;
+                    }
+                }
+                print_stop(&mut debugger, stop);
+            }
+            "c" | "continue" => {
+                let stop = debugger.resume(Resume::Continue);
+                print_stop(&mut debugger, stop);
+            }
+            "f" | "finish" => {
+                let stop = debugger.resume(Resume::Finish);
+                print_stop(&mut debugger, stop);
+            }
+            "b" | "break" => match parse::<usize>(words.get(1)) {
+                Some(address) if debugger.toggle_breakpoint(address) => {
+                    println!("Breakpoint set at {}", address)
+                }
+                Some(address) => println!("Breakpoint removed from {}", address),
+                None => println!("Usage: break <addr>"),
+            },
+            "bo" => match parse::<i128>(words.get(1)) {
+                Some(opcode) if debugger.toggle_opcode_breakpoint(opcode) => {
+                    println!("Breakpoint set on opcode {}", opcode)
+                }
+                Some(opcode) => println!("Breakpoint removed from opcode {}", opcode),
+                None => println!("Usage: bo <opcode>"),
+            },
+            "w" | "watch" => match parse::<usize>(words.get(1)) {
+                Some(address) if debugger.toggle_watchpoint(address) => {
+                    println!("Watching [{}]", address)
+                }
+                Some(address) => println!("Stopped watching [{}]", address),
+                None => println!("Usage: watch <addr>"),
+            },
+            "i" | "input" => {
+                for word in &words[1..] {
+                    match word.trim_matches(',').parse::<i128>() {
+                        Ok(value) => debugger.push_input(value),
+                        Err(_) => println!("Invalid input {}", word),
+                    }
+                }
+            }
+            "x" => match parse::<usize>(words.get(1)).and_then(|address| {
+                let count = parse::<usize>(words.get(2)).unwrap_or(8);
+                cell_range(address, count, 1)
+            }) {
+                Some(range) => {
+                    let address = range.start;
+                    let values = debugger.computer().memory_range(range);
+                    for (row, chunk) in values.chunks(8).enumerate() {
+                        let cells: Vec<String> = chunk.iter().map(|v| v.to_string()).collect();
+                        println!("{:>6}: {}", address + row * 8, cells.join(" "));
+                    }
+                }
+                None => println!("Usage: x <addr> [count], at most {} cells", MAX_COUNT),
+            },
+            "poke" => match (parse::<usize>(words.get(1)), parse::<i128>(words.get(2))) {
+                (Some(address), Some(value)) => debugger.poke(address, value),
+                _ => println!("Usage: poke <addr> <value>"),
+            },
+            "l" | "list" => {
+                let from = parse::<usize>(words.get(1)).unwrap_or(debugger.computer().pointer());
+                let count = parse::<usize>(words.get(2)).unwrap_or(10);
+                print_listing(&debugger, from, count);
+            }
+            "r" | "regs" => print_registers(&debugger),
+            "info" => {
+                println!("Breakpoints: {:?}", debugger.breakpoints());
+                println!("Opcode breakpoints: {:?}", debugger.opcode_breakpoints());
+                println!("Watchpoints: {:?}", debugger.watchpoints());
+            }
+            "smc" => {
+                let monitor = debugger.computer().code_monitor().unwrap();
+                if !monitor.is_self_modifying() {
+                    println!("No writes into executed code");
+                }
+                for write in monitor.code_writes() {
+                    println!("{}", write);
+                }
+            }
+            "h" | "help" => println!("{}", HELP),
+            "q" | "quit" => break,
+            _ => println!("Unknown command {}, try 'help'", command),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+#[path = "../common.rs"]
+#[allow(dead_code)]
+mod common;
+#[path = "../intcode/mod.rs"]
+#[allow(dead_code)]
+mod intcode;
+
+use intcode::io::QueueIo;
+use intcode::{load_program, IntcodeComputer, IntcodeReturn};
+use std::env;
+
+const USAGE: &str = "Usage: intcode_profiler <program file> [--top N] [--listing] [inputs...]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let filepath = match args.first() {
+        Some(filepath) => filepath,
+        None => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+    let mut top = 20;
+    let mut show_listing = false;
+    let mut inputs = Vec::new();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--top" => match rest.next().and_then(|v| v.parse::<usize>().ok()) {
+                Some(value) => top = value,
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            "--listing" => show_listing = true,
+            _ => match arg.trim_matches(',').parse::<i128>() {
+                Ok(value) => inputs.push(value),
+                Err(_) => {
+                    println!("Invalid input {}. {}", arg, USAGE);
+                    return;
+                }
+            },
+        }
+    }
+
+    let program = load_program(filepath);
+    let mut computer = IntcodeComputer::new(program.clone());
+    computer.enable_profiler();
+    let mut io = QueueIo::new(inputs);
+    match computer.run_with_io(&mut io) {
+        Ok(IntcodeReturn::End) => {}
+        Ok(_) => println!("Stopped waiting for input"),
+        Err(error) => println!("Error: {}", error),
+    }
+    println!("Outputs: {:?}", io.outputs);
+
+    let profile = computer.take_profile().unwrap();
+    println!("{}", profile.report(&program, top));
+    if show_listing {
+        println!("{}", profile.annotated_listing(&program));
+    }
+}
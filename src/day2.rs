@@ -1,87 +1,116 @@
-use crate::common::{get_input, to_int_vec};
-use std::convert::TryInto;
-
-pub struct IntcodeComputer {
-    memory: Vec<i64>,
-}
-
-impl IntcodeComputer {
-    pub fn run(mut self, arg1: i64, arg2: i64) -> Result<i64, &'static str> {
-        let mut pointer = 0;
-        self.memory[1] = arg1;
-        self.memory[2] = arg2;
-
-        loop {
-            let opcode = self.memory[pointer];
-            if opcode == 99 {
-                break;
-            }
-            let val1_position: usize = self.memory[pointer + 1].try_into().unwrap();
-            let val2_position: usize = self.memory[pointer + 2].try_into().unwrap();
-            let val1 = self.memory[val1_position];
-            let val2 = self.memory[val2_position];
-            let val3: usize = self.memory[pointer + 3].try_into().unwrap();
-            match opcode {
-                1 => {
-                    self.memory[val3] = val1 + val2;
-                    //println!("[{}] = {} + {}", val3, val1, val2)
-                }
-                2 => {
-                    self.memory[val3] = val1 * val2;
-                    // println!("[{}] = {} * {}", val3, val1, val2)
-                }
-                _ => println!("Unknow opcode {}", opcode),
-            }
-            pointer += 4;
-        }
-
-        Ok(self.memory[0])
-    }
-}
-
-pub fn star1() {
-    let input = to_int_vec(get_input("inputs/day2.txt", ","));
-    let computer = IntcodeComputer { memory: input };
-    let result = computer.run(12, 2);
-    match result {
-        Ok(value) => println!("Day 2 Star 1: {}", value),
-        Err(error) => println!("Error"),
-    }
-}
-
-pub fn star2() {
-    let input = to_int_vec(get_input("inputs/day2.txt", ","));
-
-    let expected_result = 19690720;
-    let mut result_found = false;
-    let mut args = (0, 0);
-    for arg1 in 1..input.len() {
-        for arg2 in 1..input.len() {
-            args = (arg1.try_into().unwrap(), arg2.try_into().unwrap());
-            print!("Args: {} {} ", arg1, arg2);
-            let computer = IntcodeComputer {
-                memory: input.clone(),
-            };
-            let result = computer.run(args.0, args.1);
-            match result {
-                Ok(value) => {
-                    println!("Result: {}", value);
-                    if value == expected_result {
-                        result_found = true;
-                        break;
-                    }
-                }
-                Err(error) => println!(" {}", error),
-            }
-        }
-        if result_found {
-            break;
-        }
-    }
-    println!(
-        "Day 2 Star 2: 100 * {} + {} = {}",
-        args.0,
-        args.1,
-        args.0 * 100 + args.1
-    );
-}
+use crate::intcode::snapshot::Snapshot;
+use crate::intcode::symbolic::{Expr, SymbolicComputer, SymbolicError};
+use crate::intcode::{load_program, IntcodeComputer, IntcodeReturn};
+
+// Day 2 programs run straight through, so anything longer is looping
+const STEP_LIMIT: usize = 100_000;
+
+pub fn run_program(base: &Snapshot, arg1: i128, arg2: i128) -> Result<i128, String> {
+    let mut computer = IntcodeComputer::from_snapshot(base.clone());
+    computer.set_step_limit(STEP_LIMIT);
+    computer.write(1, arg1);
+    computer.write(2, arg2);
+
+    match computer.run() {
+        Ok(IntcodeReturn::End) => Ok(computer.read(0)),
+        Ok(IntcodeReturn::WaitForInput) => Err(String::from("Program asked for input")),
+        Ok(_) => Err(String::from("Program produced output")),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+pub fn star1() {
+    let input = load_program("inputs/day2.txt");
+    let result = run_program(&IntcodeComputer::new(input).snapshot(), 12, 2);
+    match result {
+        Ok(value) => println!("Day 2 Star 1: {}", value),
+        Err(error) => println!("Error {}", error),
+    }
+}
+
+// Nouns and verbs are between 0 and 99
+const MAX_ARGUMENT: i128 = 99;
+
+fn probe(base: &Snapshot, noun: i128, verb: i128) -> Option<i128> {
+    run_program(base, noun, verb).ok()
+}
+
+// Address 0 at halt in terms of the noun and verb, like 460800*noun + verb + 2
+pub fn output_expression(base: &Snapshot) -> Result<Expr, SymbolicError> {
+    let mut computer = SymbolicComputer::from_snapshot(base);
+    computer.set_step_limit(STEP_LIMIT);
+    computer.bind(1, "noun");
+    computer.bind(2, "verb");
+    computer.run()?;
+    Ok(computer.read(0))
+}
+
+fn symbolic_model(base: &Snapshot) -> Option<(i128, i128, i128)> {
+    let (coefficients, c) = output_expression(base)
+        .ok()?
+        .linear_coefficients(&["noun", "verb"])?;
+    Some((coefficients[0], coefficients[1], c))
+}
+
+// Fits output = a * noun + b * verb + c and checks it against a few more points
+fn linear_model(base: &Snapshot, max: i128) -> Option<(i128, i128, i128)> {
+    let c = probe(base, 0, 0)?;
+    let a = probe(base, 1, 0)? - c;
+    let b = probe(base, 0, 1)? - c;
+    let checks = [(1, 1), (max, 0), (0, max), (max / 2, max / 3), (max, max)];
+    for (noun, verb) in checks.iter() {
+        if probe(base, *noun, *verb)? != a * noun + b * verb + c {
+            return None;
+        }
+    }
+    Some((a, b, c))
+}
+
+fn solve_linear(a: i128, b: i128, c: i128, target: i128, max: i128) -> Option<(i128, i128)> {
+    (0..=max).find_map(|noun| {
+        let rest = target - c - a * noun;
+        if b == 0 {
+            return if rest == 0 { Some((noun, 0)) } else { None };
+        }
+        let verb = rest / b;
+        if rest % b == 0 && verb >= 0 && verb <= max {
+            Some((noun, verb))
+        } else {
+            None
+        }
+    })
+}
+
+pub fn solve_noun_verb(base: &Snapshot, target: i128, max: i128) -> Option<(i128, i128)> {
+    let model = symbolic_model(base).or_else(|| linear_model(base, max));
+    if let Some((a, b, c)) = model {
+        if let Some((noun, verb)) = solve_linear(a, b, c, target, max) {
+            if probe(base, noun, verb) == Some(target) {
+                return Some((noun, verb));
+            }
+        }
+    }
+
+    // Not linear, or the model missed: try every pair
+    (0..=max)
+        .flat_map(|noun| (0..=max).map(move |verb| (noun, verb)))
+        .find(|(noun, verb)| probe(base, *noun, *verb) == Some(target))
+}
+
+pub fn star2() {
+    let input = load_program("inputs/day2.txt");
+    let base = IntcodeComputer::new(input).snapshot();
+
+    if let Ok(expression) = output_expression(&base) {
+        println!("Day 2: memory[0] = {}", expression);
+    }
+    match solve_noun_verb(&base, 19690720, MAX_ARGUMENT) {
+        Some((noun, verb)) => println!(
+            "Day 2 Star 2: 100 * {} + {} = {}",
+            noun,
+            verb,
+            noun * 100 + verb
+        ),
+        None => println!("Day 2 Star 2: no noun and verb found"),
+    }
+}
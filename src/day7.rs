@@ -1,309 +1,322 @@
-use crate::common::{get_input, to_int_vec};
-use permutator::Permutation;
-use std::convert::TryInto;
-use std::io::stdin;
+use crate::intcode::network::{chain, ring, Network, NetworkEvent, Wiring};
+use crate::intcode::snapshot::Snapshot;
+use crate::intcode::threaded::ThreadedNetwork;
+use crate::intcode::{load_program, IntcodeComputer, IntcodeError};
+use crate::permutations::{heap_permutations, k_permutations};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub struct IntcodeComputer {
-    memory: Vec<i128>,
-    pointer: usize,
+// Instructions each amplifier may run, so a bad phase setting can't hang a sweep
+const STEP_LIMIT: usize = 1_000_000;
+
+// Every amplifier shares the program image until it writes to it
+fn amplifiers(
+    input_settings: &[i128],
+    software: &Snapshot,
+    step_limit: usize,
+) -> Vec<IntcodeComputer> {
+    input_settings
+        .iter()
+        .map(|setting| {
+            let mut computer = IntcodeComputer::from_snapshot(software.clone());
+            computer.set_step_limit(step_limit);
+            computer.push_input(*setting);
+            computer
+        })
+        .collect()
 }
 
-#[derive(Debug)]
-pub enum Parameter {
-    Immediate(i128),
-    Position(usize),
-    Unknown,
+fn run_amplifiers(
+    input_settings: &[i128],
+    software: &Snapshot,
+    edges: Vec<(usize, usize)>,
+    step_limit: usize,
+) -> Result<i128, IntcodeError> {
+    if input_settings.is_empty() {
+        return Ok(0);
+    }
+    let last = input_settings.len() - 1;
+    let computers = amplifiers(input_settings, software, step_limit);
+    let mut network = Network::new(computers, Wiring::Direct(edges));
+    network.send(0, &[0]);
+    match network.run()? {
+        NetworkEvent::Halted => {}
+        NetworkEvent::Idle | NetworkEvent::External { .. } => {
+            return Err(IntcodeError::NetworkIdle)
+        }
+    }
+    network
+        .outputs(last)
+        .last()
+        .cloned()
+        .ok_or(IntcodeError::MissingOutput { machine: last })
 }
 
-#[derive(Debug, PartialEq)]
-pub enum IntcodeReturn {
-    WaitForInput,
-    Output(i128),
-    Continue,
-    End,
+pub fn run_thrusters(input_settings: &[i128], software: &Snapshot) -> Result<i128, IntcodeError> {
+    let edges = chain(input_settings.len());
+    run_amplifiers(input_settings, software, edges, STEP_LIMIT)
 }
 
-#[derive(Debug)]
-pub enum Instruction {
-    Add(Parameter, Parameter, usize),
-    Multiply(Parameter, Parameter, usize),
-    Input(usize),
-    Output(usize),
-    JumpIfTrue(Parameter, Parameter),
-    JumpIfFalse(Parameter, Parameter),
-    LessThan(Parameter, Parameter, usize),
-    Equals(Parameter, Parameter, usize),
-    Break,
-    Unknown,
+pub fn run_with_feedback(
+    input_settings: &[i128],
+    software: &Snapshot,
+) -> Result<i128, IntcodeError> {
+    let edges = ring(input_settings.len());
+    run_amplifiers(input_settings, software, edges, STEP_LIMIT)
 }
 
-impl IntcodeComputer {
-    pub fn get_next_instruction(&self) -> Instruction {
-        let instruction_code = self.memory[self.pointer];
-        match instruction_code % 100 {
-            1 => Instruction::Add(
-                self.get_parameter(instruction_code, 1),
-                self.get_parameter(instruction_code, 2),
-                self.memory[self.pointer + 3].try_into().unwrap(),
-            ),
-            2 => Instruction::Multiply(
-                self.get_parameter(instruction_code, 1),
-                self.get_parameter(instruction_code, 2),
-                self.memory[self.pointer + 3].try_into().unwrap(),
-            ),
-            3 => Instruction::Input(self.memory[self.pointer + 1].try_into().unwrap()),
-            4 => Instruction::Output(self.memory[self.pointer + 1].try_into().unwrap()),
-            5 => Instruction::JumpIfTrue(
-                self.get_parameter(instruction_code, 1),
-                self.get_parameter(instruction_code, 2),
-            ),
-            6 => Instruction::JumpIfFalse(
-                self.get_parameter(instruction_code, 1),
-                self.get_parameter(instruction_code, 2),
-            ),
-            7 => Instruction::LessThan(
-                self.get_parameter(instruction_code, 1),
-                self.get_parameter(instruction_code, 2),
-                self.memory[self.pointer + 3].try_into().unwrap(),
-            ),
-            8 => Instruction::Equals(
-                self.get_parameter(instruction_code, 1),
-                self.get_parameter(instruction_code, 2),
-                self.memory[self.pointer + 3].try_into().unwrap(),
-            ),
-            99 => Instruction::Break,
-            _ => Instruction::Unknown,
-        }
+pub fn run_with_feedback_threaded(
+    input_settings: &[i128],
+    software: &Snapshot,
+) -> Result<i128, IntcodeError> {
+    if input_settings.is_empty() {
+        return Ok(0);
+    }
+    let mut computers = amplifiers(input_settings, software, STEP_LIMIT);
+    if let Some(first) = computers.first_mut() {
+        first.push_input(0);
     }
 
-    pub fn get_parameter(&self, instruction_code: i128, position: u32) -> Parameter {
-        let position_u: usize = position.try_into().unwrap();
-        let pos_multiplier: i128 = (10 as i128).pow(position);
-        let mode: i128 = (instruction_code % (100 * pos_multiplier)) / (10 * pos_multiplier);
-        match mode {
-            0 => Parameter::Position(self.memory[self.pointer + position_u].try_into().unwrap()),
-            1 => Parameter::Immediate(self.memory[self.pointer + position_u]),
-            _ => panic!(
-                "Unknown mode {} for instruction code {} param number {}",
-                mode, instruction_code, position
-            ),
-        }
+    let last = computers.len().saturating_sub(1);
+    let outputs = ThreadedNetwork::ring(computers).run()?;
+    outputs
+        .last()
+        .and_then(|o| o.last())
+        .cloned()
+        .ok_or(IntcodeError::MissingOutput { machine: last })
+}
+
+pub struct PhaseSearch {
+    // Every setting that ran successfully, best output first
+    pub ranking: Vec<(Vec<i128>, i128)>,
+    pub runs: usize,
+    pub threads: usize,
+    pub elapsed: Duration,
+}
+
+fn settings_string(settings: &[i128]) -> String {
+    settings
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+impl PhaseSearch {
+    pub fn best(&self) -> Option<&(Vec<i128>, i128)> {
+        self.ranking.first()
     }
+}
 
-    pub fn fetch_data(&self, param: Parameter) -> i128 {
-        match param {
-            Parameter::Immediate(value) => value,
-            Parameter::Position(pos) => self.memory[pos],
-            Parameter::Unknown => panic!("Unknow data"),
+impl fmt::Display for PhaseSearch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.best() {
+            Some((settings, output)) => write!(
+                f,
+                "Max output {}. Settings {}. ",
+                output,
+                settings_string(settings)
+            )?,
+            None => write!(f, "No setting ran successfully. ")?,
+        }
+        write!(
+            f,
+            "{} runs on {} threads in {:?}",
+            self.runs, self.threads, self.elapsed
+        )?;
+        // Errors include runs stopped by the step limit
+        let failed = self.runs - self.ranking.len();
+        if failed > 0 {
+            write!(f, ", {} failed", failed)?;
         }
+        Ok(())
     }
+}
 
-    pub fn run(&mut self, input: Option<i128>) -> IntcodeReturn {
-        let mut current_input = input;
-        //println!("Run with {:?}", input);
-        loop {
-            let instruction = self.get_next_instruction();
-            let result = match instruction {
-                Instruction::Add(p1, p2, p3) => {
-                    self.memory[p3] = self.fetch_data(p1) + self.fetch_data(p2);
-                    self.pointer += 4;
-                    IntcodeReturn::Continue
-                }
-                Instruction::Multiply(p1, p2, p3) => {
-                    self.memory[p3] = self.fetch_data(p1) * self.fetch_data(p2);
-                    self.pointer += 4;
-                    IntcodeReturn::Continue
-                }
-                Instruction::Input(p1) => match current_input {
-                    Some(value) => {
-                        self.memory[p1] = value;
-                        self.pointer += 2;
-                        current_input = None;
-                        IntcodeReturn::Continue
-                    }
-                    None => IntcodeReturn::WaitForInput,
-                },
-                Instruction::Output(p1) => {
-                    let output = IntcodeReturn::Output(self.memory[p1]);
-                    self.pointer += 2;
-                    output
-                }
-                Instruction::JumpIfTrue(p1, p2) => {
-                    if self.fetch_data(p1) != 0 {
-                        self.pointer = self.fetch_data(p2).try_into().unwrap();
-                    } else {
-                        self.pointer += 3;
-                    }
-                    IntcodeReturn::Continue
-                }
-                Instruction::JumpIfFalse(p1, p2) => {
-                    if self.fetch_data(p1) == 0 {
-                        self.pointer = self.fetch_data(p2).try_into().unwrap();
-                    } else {
-                        self.pointer += 3;
-                    }
-                    IntcodeReturn::Continue
-                }
-                Instruction::LessThan(p1, p2, dest) => {
-                    if self.fetch_data(p1) < self.fetch_data(p2) {
-                        self.memory[dest] = 1;
-                    } else {
-                        self.memory[dest] = 0;
-                    }
-                    self.pointer += 4;
-                    IntcodeReturn::Continue
-                }
-                Instruction::Equals(p1, p2, dest) => {
-                    if self.fetch_data(p1) == self.fetch_data(p2) {
-                        self.memory[dest] = 1;
-                    } else {
-                        self.memory[dest] = 0;
-                    }
-                    self.pointer += 4;
-                    IntcodeReturn::Continue
-                }
-                Instruction::Break => IntcodeReturn::End,
-                _ => panic!("Unknow opcode instruction"),
-            };
+// Workers share one program image and pull the next permutation to try from a common counter
+pub fn search_phases<F>(permutations: &[Vec<i128>], software: &[i128], run: F) -> PhaseSearch
+where
+    F: Fn(&[i128], &Snapshot) -> Result<i128, IntcodeError> + Sync,
+{
+    let start = Instant::now();
+    let software = IntcodeComputer::new(software.to_vec()).snapshot();
+    let software = &software;
+    let threads = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(permutations.len())
+        .max(1);
+    let next = AtomicUsize::new(0);
 
-            //println!("next is {:?}", result);
+    let mut results: Vec<(usize, i128)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= permutations.len() {
+                            return results;
+                        }
+                        if let Ok(output) = run(&permutations[index], software) {
+                            results.push((index, output));
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Phase search worker panicked"))
+            .collect()
+    });
+    results.sort_by_key(|(index, output)| (std::cmp::Reverse(*output), *index));
 
-            if let IntcodeReturn::Continue = result {
-                continue;
-            } else {
-                return result;
-            }
-        }
+    PhaseSearch {
+        ranking: results
+            .into_iter()
+            .map(|(index, output)| (permutations[index].clone(), output))
+            .collect(),
+        runs: permutations.len(),
+        threads,
+        elapsed: start.elapsed(),
     }
 }
 
-pub fn run_thrusters(input_settings: Vec<i128>, software: Vec<i128>) -> i128 {
-    let mut previous_output: i128 = 0;
-    for setting in input_settings {
-        let mut computer = IntcodeComputer {
-            memory: software.clone(),
-            pointer: 0,
-        };
+pub struct AmplifierOptions {
+    pub stages: usize,
+    pub phases: Vec<i128>,
+    pub feedback: bool,
+    pub input: String,
+    pub top: usize,
+    pub step_limit: usize,
+}
 
-        let mut inputs: Vec<i128> = vec![previous_output, setting];
-        let mut result = computer.run(inputs.pop());
-        loop {
-            //println!("Got {:?}", result);
-            let mut next_input = None;
-            match result {
-                IntcodeReturn::Output(value) => {
-                    previous_output = value;
-                }
-                IntcodeReturn::End => {
-                    break;
-                }
-                IntcodeReturn::WaitForInput => {
-                    next_input = inputs.pop();
-                }
-                _ => continue,
-            }
-            //println!("Next input is: {:?}", next_input);
-            result = computer.run(next_input);
+impl Default for AmplifierOptions {
+    fn default() -> AmplifierOptions {
+        AmplifierOptions {
+            stages: 5,
+            phases: vec![0, 1, 2, 3, 4],
+            feedback: false,
+            input: String::from("inputs/day7.txt"),
+            top: 10,
+            step_limit: STEP_LIMIT,
         }
     }
-    return previous_output;
 }
 
-pub fn run_with_feedback(input_settings: Vec<i128>, software: Vec<i128>) -> i128 {
-    let mut previous_output: i128 = 0;
-    let mut computers: Vec<IntcodeComputer> = input_settings
-        .iter()
-        .map(|_| IntcodeComputer {
-            memory: software.clone(),
-            pointer: 0,
-        })
-        .collect();
+const USAGE: &str =
+    "Usage: day7 [--stages N] [--phases 5,6,7,8,9] [--feedback] [--input FILE] [--top N] [--step-limit N]";
 
-    //Init with settings
-    for i in 0..5 {
-        computers[i].run(Some(input_settings[i]));
+pub fn parse_options(args: &[String]) -> Result<AmplifierOptions, String> {
+    let mut options = AmplifierOptions::default();
+    let mut stages = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}. {}", arg, USAGE))
+        };
+        match arg.as_str() {
+            "--stages" => {
+                stages = Some(
+                    value()?
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid stage count. {}", USAGE))?,
+                )
+            }
+            "--phases" => {
+                options.phases = value()?
+                    .split(',')
+                    .map(|phase| phase.trim().parse::<i128>())
+                    .collect::<Result<Vec<i128>, _>>()
+                    .map_err(|_| format!("Invalid phase list. {}", USAGE))?
+            }
+            "--input" => options.input = value()?.clone(),
+            "--top" => {
+                options.top = value()?
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid top count. {}", USAGE))?
+            }
+            "--step-limit" => {
+                options.step_limit = value()?
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid step limit. {}", USAGE))?
+            }
+            "--feedback" => options.feedback = true,
+            _ => return Err(format!("Unknown option {}. {}", arg, USAGE)),
+        }
     }
+    options.stages = stages.unwrap_or(options.phases.len());
+    if options.stages == 0 || options.stages > options.phases.len() {
+        return Err(format!(
+            "Need between 1 and {} stages for {} phases",
+            options.phases.len(),
+            options.phases.len()
+        ));
+    }
+    Ok(options)
+}
 
-    let mut previous_outputs: Vec<IntcodeReturn> = vec![
-        IntcodeReturn::Output(0),
-        IntcodeReturn::Output(0),
-        IntcodeReturn::Output(0),
-        IntcodeReturn::Output(0),
-        IntcodeReturn::Output(0),
-    ];
-
-    let mut last_good_output = 0;
-    loop {
-        for current_computer_index in 0..5 {
-            let computer = &mut computers[current_computer_index];
-            let previous_computer_index = if current_computer_index == 0 {
-                4
-            } else {
-                current_computer_index - 1
-            };
-
-            if let IntcodeReturn::Output(output) = previous_outputs[previous_computer_index] {
-                previous_outputs[current_computer_index] = computer.run(Some(output));
-            } else {
-                previous_outputs[current_computer_index] = computer.run(None);
-            }
+// Tries every ordered choice of one phase per stage
+pub fn rank_amplifiers(options: &AmplifierOptions, software: &[i128]) -> PhaseSearch {
+    let permutations: Vec<Vec<i128>> =
+        k_permutations(options.phases.clone(), options.stages).collect();
+    let step_limit = options.step_limit;
+    search_phases(&permutations, software, |settings, software| {
+        let edges = if options.feedback {
+            ring(settings.len())
+        } else {
+            chain(settings.len())
+        };
+        run_amplifiers(settings, software, edges, step_limit)
+    })
+}
 
-            //println!("Got {:?}", previous_outputs[current_computer_index]);
-        }
-        if let IntcodeReturn::Output(value) = previous_outputs[4] {
-            last_good_output = value;
-        }
-        if previous_outputs.iter().all(|o| IntcodeReturn::End == *o) {
-            //println!("Final output {}",last_good_output );
-            return last_good_output;
+pub fn run_cli(args: &[String]) {
+    let options = match parse_options(args) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("{}", error);
+            return;
         }
+    };
+    let input = load_program(&options.input);
+    let result = rank_amplifiers(&options, &input);
+    println!("{}", result);
+    for (rank, (settings, output)) in result.ranking.iter().take(options.top).enumerate() {
+        println!(
+            "{:>4}. {} -> {}",
+            rank + 1,
+            settings_string(settings),
+            output
+        );
     }
 }
 
 pub fn star1() {
-    let input = to_int_vec(get_input("inputs/day7.txt", ","))
-        .iter()
-        .map(|x| *x as i128)
-        .collect::<Vec<i128>>();
-    let possible_settings: Vec<i128> = vec![4, 3, 2, 1, 0];
-    let mut all_permutations: Vec<Vec<i128>> = possible_settings.clone().permutation().collect();
-    all_permutations.push(possible_settings); //permutation() doesn't output the first permutation!!
-    let result = all_permutations
-        .iter()
-        .map(|v| (v.clone(), run_thrusters(v.clone(), input.clone())))
-        .max_by_key(|s| s.1)
-        .unwrap();
-
-    println!(
-        "Max output {}. Settings {}",
-        result.1,
-        result
-            .0
-            .iter()
-            .fold(String::new(), |s, v| s + &v.to_string())
-    );
+    let input = load_program("inputs/day7.txt");
+    let options = AmplifierOptions::default();
+    println!("Day 7 Star 1: {}", rank_amplifiers(&options, &input));
 }
 
 pub fn star2() {
-    let input = to_int_vec(get_input("inputs/day7.txt", ","))
-        .iter()
-        .map(|x| *x as i128)
-        .collect::<Vec<i128>>();
-    let possible_settings: Vec<i128> = vec![9, 8, 7, 6, 5];
-    let mut all_permutations: Vec<Vec<i128>> = possible_settings.clone().permutation().collect();
-    all_permutations.push(possible_settings); //permutation() doesn't output the first permutation!!
-    let result = all_permutations
-        .iter()
-        .map(|v| (v.clone(), run_with_feedback(v.clone(), input.clone())))
-        .max_by_key(|s| s.1)
-        .unwrap();
+    let input = load_program("inputs/day7.txt");
+    let options = AmplifierOptions {
+        phases: vec![5, 6, 7, 8, 9],
+        feedback: true,
+        ..AmplifierOptions::default()
+    };
+    println!("Day 7 Star 2: {}", rank_amplifiers(&options, &input));
+}
 
+pub fn star2_threaded() {
+    let input = load_program("inputs/day7.txt");
+    let permutations = heap_permutations(vec![9, 8, 7, 6, 5]).collect::<Vec<Vec<i128>>>();
     println!(
-        "Max output {}. Settings {}",
-        result.1,
-        result
-            .0
-            .iter()
-            .fold(String::new(), |s, v| s + &v.to_string())
+        "Day 7 Star 2 (threaded machines): {}",
+        search_phases(&permutations, &input, run_with_feedback_threaded)
     );
 }
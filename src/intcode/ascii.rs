@@ -0,0 +1,106 @@
+use super::{IntcodeComputer, IntcodeError, IntcodeReturn};
+use std::io::{stdin, stdout, Write};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AsciiOutput {
+    Line(String),
+    // Anything outside the ASCII range, like a puzzle answer
+    Value(i128),
+}
+
+// Talks to a program in text: input is fed as character codes and output is
+// gathered into lines, ending at each newline
+pub struct AsciiComputer {
+    computer: IntcodeComputer,
+    line: String,
+    output: Vec<AsciiOutput>,
+}
+
+impl AsciiComputer {
+    pub fn new(computer: IntcodeComputer) -> AsciiComputer {
+        AsciiComputer {
+            computer,
+            line: String::new(),
+            output: Vec::new(),
+        }
+    }
+
+    pub fn computer(&self) -> &IntcodeComputer {
+        &self.computer
+    }
+
+    pub fn computer_mut(&mut self) -> &mut IntcodeComputer {
+        &mut self.computer
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.computer.push_inputs(text.bytes().map(i128::from));
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        self.push_str(line);
+        self.push_str("\n");
+    }
+
+    // Output collected so far, without the line still being written
+    pub fn take_output(&mut self) -> Vec<AsciiOutput> {
+        self.output.drain(..).collect()
+    }
+
+    // Text after the last newline, usually a prompt
+    pub fn partial_line(&self) -> &str {
+        &self.line
+    }
+
+    fn collect(&mut self, value: i128) {
+        match value {
+            10 => {
+                let line = std::mem::take(&mut self.line);
+                self.output.push(AsciiOutput::Line(line));
+            }
+            0..=127 => self.line.push(value as u8 as char),
+            _ => self.output.push(AsciiOutput::Value(value)),
+        }
+    }
+
+    // Runs until the program halts or needs more input than was pushed
+    pub fn run(&mut self) -> Result<IntcodeReturn, IntcodeError> {
+        loop {
+            match self.computer.run()? {
+                IntcodeReturn::Output(value) => self.collect(value),
+                result => return Ok(result),
+            }
+        }
+    }
+
+    // Prints the program's output and reads a line from the terminal whenever it
+    // waits for input, until it halts or the terminal can't be read or written
+    pub fn interactive(&mut self) -> Result<(), IntcodeError> {
+        loop {
+            let result = self.run()?;
+            for output in self.take_output() {
+                match output {
+                    AsciiOutput::Line(line) => println!("{}", line),
+                    AsciiOutput::Value(value) => println!("{}", value),
+                }
+            }
+            if result == IntcodeReturn::End {
+                if !self.line.is_empty() {
+                    println!("{}", self.line);
+                }
+                return Ok(());
+            }
+
+            print!("{}", self.line);
+            self.line.clear();
+            if stdout().flush().is_err() {
+                return Ok(());
+            }
+            let mut buffer = String::new();
+            if stdin().read_line(&mut buffer).unwrap_or(0) == 0 {
+                return Ok(());
+            }
+            self.push_line(buffer.trim_end_matches(['\n', '\r']));
+        }
+    }
+}
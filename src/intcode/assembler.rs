@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+// Mnemonics match the ones printed by the disassembler
+const OPCODES: [(&str, i128, usize); 10] = [
+    ("add", 1, 3),
+    ("mul", 2, 3),
+    ("in", 3, 1),
+    ("out", 4, 1),
+    ("jt", 5, 2),
+    ("jf", 6, 2),
+    ("lt", 7, 3),
+    ("eq", 8, 3),
+    ("arb", 9, 1),
+    ("hlt", 99, 0),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblerError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssemblerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AssemblerError {}
+
+#[derive(Debug)]
+enum Value {
+    Number(i128),
+    Label(String),
+}
+
+#[derive(Debug)]
+enum Operand {
+    Immediate(Value),
+    Position(Value),
+    Relative(Value),
+}
+
+#[derive(Debug)]
+enum Statement {
+    Instruction(i128, Vec<Operand>),
+    Data(Vec<Value>),
+}
+
+impl Statement {
+    fn length(&self) -> usize {
+        match self {
+            Statement::Instruction(_, operands) => operands.len() + 1,
+            Statement::Data(values) => values.len(),
+        }
+    }
+}
+
+fn error(line: usize, message: String) -> AssemblerError {
+    AssemblerError { line, message }
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_value(text: &str, line: usize) -> Result<Value, AssemblerError> {
+    let text = text.trim();
+    if let Ok(number) = text.parse::<i128>() {
+        Ok(Value::Number(number))
+    } else if is_label(text) {
+        Ok(Value::Label(text.to_string()))
+    } else {
+        Err(error(line, format!("Invalid value '{}'", text)))
+    }
+}
+
+fn parse_operand(text: &str, line: usize) -> Result<Operand, AssemblerError> {
+    let text = text.trim();
+    if let Some(value) = text.strip_prefix('#') {
+        Ok(Operand::Immediate(parse_value(value, line)?))
+    } else if let Some(value) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        Ok(Operand::Position(parse_value(value, line)?))
+    } else if let Some(value) = text.strip_prefix("rb") {
+        let value = value.trim();
+        match value.strip_prefix('+') {
+            Some(positive) => Ok(Operand::Relative(parse_value(positive, line)?)),
+            None if value.starts_with('-') => Ok(Operand::Relative(parse_value(value, line)?)),
+            None => Err(error(line, format!("Invalid relative operand '{}'", text))),
+        }
+    } else {
+        Err(error(line, format!("Invalid operand '{}'", text)))
+    }
+}
+
+fn split_list(text: &str) -> Vec<&str> {
+    if text.trim().is_empty() {
+        vec![]
+    } else {
+        text.split(',').collect()
+    }
+}
+
+fn parse_statement(text: &str, line: usize) -> Result<Statement, AssemblerError> {
+    let (mnemonic, rest) = match text.find(char::is_whitespace) {
+        Some(index) => (&text[..index], &text[index..]),
+        None => (text, ""),
+    };
+    let mnemonic = mnemonic.to_lowercase();
+
+    if mnemonic == "data" {
+        let values = split_list(rest)
+            .iter()
+            .map(|value| parse_value(value, line))
+            .collect::<Result<Vec<Value>, AssemblerError>>()?;
+        if values.is_empty() {
+            return Err(error(line, String::from("Empty data directive")));
+        }
+        return Ok(Statement::Data(values));
+    }
+
+    let (_, opcode, count) = OPCODES
+        .iter()
+        .find(|(name, _, _)| *name == mnemonic)
+        .ok_or_else(|| error(line, format!("Unknown mnemonic '{}'", mnemonic)))?;
+    let operands = split_list(rest)
+        .iter()
+        .map(|operand| parse_operand(operand, line))
+        .collect::<Result<Vec<Operand>, AssemblerError>>()?;
+    if operands.len() != *count {
+        return Err(error(
+            line,
+            format!(
+                "'{}' takes {} operands, found {}",
+                mnemonic,
+                count,
+                operands.len()
+            ),
+        ));
+    }
+    Ok(Statement::Instruction(*opcode, operands))
+}
+
+fn resolve(
+    value: &Value,
+    labels: &HashMap<String, usize>,
+    line: usize,
+) -> Result<i128, AssemblerError> {
+    match value {
+        Value::Number(number) => Ok(*number),
+        Value::Label(name) => labels
+            .get(name)
+            .map(|address| *address as i128)
+            .ok_or_else(|| error(line, format!("Unknown label '{}'", name))),
+    }
+}
+
+pub fn assemble(source: &str) -> Result<Vec<i128>, AssemblerError> {
+    let mut labels: HashMap<String, usize> = HashMap::new();
+    let mut statements: Vec<(usize, Statement)> = Vec::new();
+    let mut address = 0;
+
+    // First pass: parse statements and assign label addresses
+    for (index, source_line) in source.lines().enumerate() {
+        let line = index + 1;
+        let mut text = source_line.split(';').next().unwrap().trim();
+
+        while let Some(colon) = text.find(':') {
+            let label = text[..colon].trim();
+            if !is_label(label) {
+                return Err(error(line, format!("Invalid label '{}'", label)));
+            }
+            if labels.insert(label.to_string(), address).is_some() {
+                return Err(error(line, format!("Duplicate label '{}'", label)));
+            }
+            text = text[colon + 1..].trim();
+        }
+
+        if text.is_empty() {
+            continue;
+        }
+        let statement = parse_statement(text, line)?;
+        address += statement.length();
+        statements.push((line, statement));
+    }
+
+    // Second pass: encode with every label known
+    let mut program = Vec::with_capacity(address);
+    for (line, statement) in statements {
+        match statement {
+            Statement::Data(values) => {
+                for value in values {
+                    program.push(resolve(&value, &labels, line)?);
+                }
+            }
+            Statement::Instruction(opcode, operands) => {
+                let mut instruction_code = opcode;
+                let mut mode_multiplier = 100;
+                let mut encoded = Vec::new();
+                for operand in operands {
+                    let (mode, value) = match operand {
+                        Operand::Position(value) => (0, value),
+                        Operand::Immediate(value) => (1, value),
+                        Operand::Relative(value) => (2, value),
+                    };
+                    instruction_code += mode * mode_multiplier;
+                    mode_multiplier *= 10;
+                    encoded.push(resolve(&value, &labels, line)?);
+                }
+                program.push(instruction_code);
+                program.extend(encoded);
+            }
+        }
+    }
+    Ok(program)
+}
@@ -0,0 +1,220 @@
+use super::{IntcodeComputer, IntcodeError, IntcodeReturn, Memory};
+use std::convert::TryFrom;
+
+// Decoded instruction: opcode, two mode bits per parameter and the raw parameter values.
+// Instructions that don't fit, like operands past i64, are left to the generic step.
+#[derive(Debug, Clone, Copy)]
+struct Op {
+    opcode: u8,
+    modes: u8,
+    operands: [i64; 3],
+}
+
+impl Op {
+    fn mode(&self, parameter: usize) -> u8 {
+        (self.modes >> (2 * parameter)) & 0b11
+    }
+
+    fn length(&self) -> usize {
+        match self.opcode {
+            1 | 2 | 7 | 8 => 4,
+            5 | 6 => 3,
+            3 | 4 | 9 => 2,
+            _ => 1,
+        }
+    }
+}
+
+// Parameters an instruction writes to, which can't be immediate
+fn writes_to(opcode: u8, parameter: usize) -> bool {
+    match opcode {
+        1 | 2 | 7 | 8 => parameter == 2,
+        3 => parameter == 0,
+        _ => false,
+    }
+}
+
+fn pack(memory: &Memory, address: usize) -> Option<Op> {
+    let code = memory.read(address);
+    let opcode = match code % 100 {
+        opcode @ 1..=9 | opcode @ 99 => opcode as u8,
+        _ => return None,
+    };
+    let mut op = Op {
+        opcode,
+        modes: 0,
+        operands: [0; 3],
+    };
+    let mut mode_digits = code / 100;
+    for parameter in 0..op.length() - 1 {
+        let mode = (mode_digits % 10) as u8;
+        let operand = i64::try_from(memory.read(address.checked_add(1 + parameter)?)).ok()?;
+        let valid = match mode {
+            0 => operand >= 0,
+            1 => !writes_to(opcode, parameter),
+            2 => true,
+            _ => false,
+        };
+        if !valid {
+            return None;
+        }
+        op.modes |= mode << (2 * parameter);
+        op.operands[parameter] = operand;
+        mode_digits /= 10;
+    }
+    Some(op)
+}
+
+// Marks an index entry that holds a decoded instruction, as opposed to a slot kept for reuse
+const VALID: u32 = 1 << 31;
+
+// Instructions decoded ahead of time by a sweep over the image, then on first use for
+// code the sweep missed. Each cell only holds an index into the packed instructions.
+// Writing to a cell drops the instructions that could cover it; their slots are reused
+// when they are decoded again, so self-modifying loops don't keep growing the program.
+pub struct DecodedProgram {
+    // 0 when never decoded, else slot + 1, with VALID set while the slot is current
+    index: Vec<u32>,
+    ops: Vec<Op>,
+}
+
+// Longest instruction is an opcode and three parameters
+const MAX_LENGTH: usize = 4;
+
+impl DecodedProgram {
+    pub fn new(memory: &Memory) -> DecodedProgram {
+        let mut decoded = DecodedProgram {
+            index: vec![0; memory.len()],
+            ops: Vec::new(),
+        };
+        let mut address = 0;
+        while address < memory.len() {
+            address += match decoded.get(memory, address) {
+                Some(op) => op.length(),
+                None => 1,
+            };
+        }
+        decoded
+    }
+
+    fn get(&mut self, memory: &Memory, address: usize) -> Option<Op> {
+        let entry = match self.index.get(address) {
+            Some(entry) => *entry,
+            // Far cells of sparse memory aren't worth an index
+            None if address >= memory.len() => return pack(memory, address),
+            None => {
+                self.index.resize(memory.len(), 0);
+                0
+            }
+        };
+        if entry & VALID != 0 {
+            return Some(self.ops[(entry & !VALID) as usize - 1]);
+        }
+        let op = pack(memory, address)?;
+        let slot = if entry == 0 {
+            self.ops.push(op);
+            self.ops.len()
+        } else {
+            self.ops[entry as usize - 1] = op;
+            entry as usize
+        };
+        self.index[address] = slot as u32 | VALID;
+        Some(op)
+    }
+
+    pub fn invalidate(&mut self, address: usize) {
+        let start = (address + 1).saturating_sub(MAX_LENGTH);
+        let end = (address + 1).min(self.index.len());
+        for entry in &mut self.index[start.min(end)..end] {
+            *entry &= !VALID;
+        }
+    }
+}
+
+impl IntcodeComputer {
+    fn operand_value(&self, op: &Op, parameter: usize) -> Result<i128, IntcodeError> {
+        let operand = i128::from(op.operands[parameter]);
+        Ok(match op.mode(parameter) {
+            0 => self.memory.read(operand as usize),
+            1 => operand,
+            _ => self.memory.read(self.relative_address(operand)?),
+        })
+    }
+
+    fn operand_address(&self, op: &Op, parameter: usize) -> Result<usize, IntcodeError> {
+        let operand = i128::from(op.operands[parameter]);
+        match op.mode(parameter) {
+            0 => Ok(operand as usize),
+            _ => self.relative_address(operand),
+        }
+    }
+
+    // Same results as calling step until it stops continuing, without the decoding and
+    // the instrumentation checks. Only used while no tracer, monitor or profiler is on.
+    pub(super) fn run_decoded(&mut self) -> Result<IntcodeReturn, IntcodeError> {
+        loop {
+            let op = match &mut self.decoded {
+                Some(decoded) => decoded.get(&self.memory, self.pointer),
+                None => None,
+            };
+            let op = match op {
+                Some(op) => op,
+                None => match self.step()? {
+                    IntcodeReturn::Continue => continue,
+                    result => return Ok(result),
+                },
+            };
+            if op.opcode == 3 && self.inputs.is_empty() {
+                return Ok(IntcodeReturn::WaitForInput);
+            }
+            if let Some(limit) = self.step_limit {
+                if self.steps >= limit {
+                    return Err(IntcodeError::StepLimitExceeded {
+                        limit,
+                        pointer: self.pointer,
+                    });
+                }
+            }
+            self.steps += 1;
+
+            match op.opcode {
+                1 | 2 | 7 | 8 => {
+                    let a = self.operand_value(&op, 0)?;
+                    let b = self.operand_value(&op, 1)?;
+                    let value = match op.opcode {
+                        1 => a.checked_add(b),
+                        2 => a.checked_mul(b),
+                        7 => Some((a < b) as i128),
+                        _ => Some((a == b) as i128),
+                    };
+                    let value = value.ok_or_else(|| self.overflow())?;
+                    let address = self.operand_address(&op, 2)?;
+                    self.write(address, value);
+                }
+                3 => {
+                    let address = self.operand_address(&op, 0)?;
+                    let value = self.inputs.pop_front().unwrap();
+                    self.write(address, value);
+                }
+                4 => {
+                    let value = self.operand_value(&op, 0)?;
+                    self.pointer += 2;
+                    return Ok(IntcodeReturn::Output(value));
+                }
+                5 | 6 => {
+                    let condition = self.operand_value(&op, 0)? != 0;
+                    if condition == (op.opcode == 5) {
+                        self.pointer = self.to_address(self.operand_value(&op, 1)?)?;
+                        continue;
+                    }
+                }
+                9 => {
+                    let base = self.relative_base.checked_add(self.operand_value(&op, 0)?);
+                    self.relative_base = base.ok_or_else(|| self.overflow())?;
+                }
+                _ => return Ok(IntcodeReturn::End),
+            }
+            self.pointer += op.length();
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use super::{IntcodeComputer, IntcodeError, IntcodeReturn};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, PartialEq)]
+pub enum Stop {
+    Stepped,
+    Breakpoint(usize),
+    OpcodeBreakpoint(i128),
+    Watchpoint {
+        address: usize,
+        old: i128,
+        new: i128,
+    },
+    WaitForInput,
+    End,
+    Error(IntcodeError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resume {
+    Step,
+    Continue,
+    // Run to the end ignoring breakpoints and watchpoints
+    Finish,
+}
+
+pub struct Debugger {
+    computer: IntcodeComputer,
+    breakpoints: HashSet<usize>,
+    opcode_breakpoints: HashSet<i128>,
+    watchpoints: HashMap<usize, i128>,
+    outputs: Vec<i128>,
+}
+
+impl Debugger {
+    pub fn new(program: Vec<i128>) -> Debugger {
+        let mut computer = IntcodeComputer::new(program);
+        computer.enable_code_monitor();
+        Debugger {
+            computer,
+            breakpoints: HashSet::new(),
+            opcode_breakpoints: HashSet::new(),
+            watchpoints: HashMap::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn computer(&self) -> &IntcodeComputer {
+        &self.computer
+    }
+
+    pub fn toggle_breakpoint(&mut self, address: usize) -> bool {
+        if !self.breakpoints.remove(&address) {
+            self.breakpoints.insert(address);
+            return true;
+        }
+        false
+    }
+
+    pub fn toggle_opcode_breakpoint(&mut self, opcode: i128) -> bool {
+        if !self.opcode_breakpoints.remove(&opcode) {
+            self.opcode_breakpoints.insert(opcode);
+            return true;
+        }
+        false
+    }
+
+    pub fn toggle_watchpoint(&mut self, address: usize) -> bool {
+        if self.watchpoints.remove(&address).is_none() {
+            self.watchpoints
+                .insert(address, self.computer.read(address));
+            return true;
+        }
+        false
+    }
+
+    pub fn breakpoints(&self) -> Vec<usize> {
+        let mut breakpoints: Vec<usize> = self.breakpoints.iter().cloned().collect();
+        breakpoints.sort();
+        breakpoints
+    }
+
+    pub fn opcode_breakpoints(&self) -> Vec<i128> {
+        let mut opcodes: Vec<i128> = self.opcode_breakpoints.iter().cloned().collect();
+        opcodes.sort();
+        opcodes
+    }
+
+    pub fn watchpoints(&self) -> Vec<usize> {
+        let mut watchpoints: Vec<usize> = self.watchpoints.keys().cloned().collect();
+        watchpoints.sort();
+        watchpoints
+    }
+
+    pub fn push_input(&mut self, value: i128) {
+        self.computer.push_input(value);
+    }
+
+    pub fn take_outputs(&mut self) -> Vec<i128> {
+        self.outputs.drain(..).collect()
+    }
+
+    pub fn poke(&mut self, address: usize, value: i128) {
+        self.computer.write(address, value);
+        if let Some(watched) = self.watchpoints.get_mut(&address) {
+            *watched = value;
+        }
+    }
+
+    fn check_breakpoints(&self) -> Option<Stop> {
+        let pointer = self.computer.pointer();
+        if self.breakpoints.contains(&pointer) {
+            return Some(Stop::Breakpoint(pointer));
+        }
+        let opcode = self.computer.read(pointer) % 100;
+        if self.opcode_breakpoints.contains(&opcode) {
+            return Some(Stop::OpcodeBreakpoint(opcode));
+        }
+        None
+    }
+
+    fn check_watchpoints(&mut self) -> Option<Stop> {
+        for (address, old) in self.watchpoints.iter_mut() {
+            let new = self.computer.read(*address);
+            if new != *old {
+                let stop = Stop::Watchpoint {
+                    address: *address,
+                    old: *old,
+                    new,
+                };
+                *old = new;
+                return Some(stop);
+            }
+        }
+        None
+    }
+
+    // Takes changes made while watchpoints were ignored as the new baseline
+    fn refresh_watchpoints(&mut self) {
+        for (address, value) in self.watchpoints.iter_mut() {
+            *value = self.computer.read(*address);
+        }
+    }
+
+    pub fn resume(&mut self, mode: Resume) -> Stop {
+        let mut first = true;
+        loop {
+            if !first && mode == Resume::Continue {
+                if let Some(stop) = self.check_breakpoints() {
+                    return stop;
+                }
+            }
+            first = false;
+
+            let stop = match self.computer.step() {
+                Ok(IntcodeReturn::Continue) => None,
+                Ok(IntcodeReturn::Output(value)) => {
+                    self.outputs.push(value);
+                    None
+                }
+                Ok(IntcodeReturn::WaitForInput) => Some(Stop::WaitForInput),
+                Ok(IntcodeReturn::End) => Some(Stop::End),
+                Err(error) => Some(Stop::Error(error)),
+            };
+            if mode != Resume::Finish {
+                if let Some(watch) = self.check_watchpoints() {
+                    return watch;
+                }
+            }
+            match stop {
+                Some(stop) => {
+                    if mode == Resume::Finish {
+                        self.refresh_watchpoints();
+                    }
+                    return stop;
+                }
+                None if mode == Resume::Step => return Stop::Stepped,
+                None => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Instruction, IntcodeComputer, Parameter};
+use std::fmt;
+
+pub struct ListingLine {
+    pub address: usize,
+    pub raw: Vec<i128>,
+    pub instruction: Option<Instruction>,
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parameter::Immediate(value) => write!(f, "#{}", value),
+            Parameter::Position(pos) => write!(f, "[{}]", pos),
+            Parameter::Relative(offset) if *offset < 0 => write!(f, "rb{}", offset),
+            Parameter::Relative(offset) => write!(f, "rb+{}", offset),
+        }
+    }
+}
+
+impl Instruction {
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Add(..) => "add",
+            Instruction::Multiply(..) => "mul",
+            Instruction::Input(_) => "in",
+            Instruction::Output(_) => "out",
+            Instruction::JumpIfTrue(..) => "jt",
+            Instruction::JumpIfFalse(..) => "jf",
+            Instruction::LessThan(..) => "lt",
+            Instruction::Equals(..) => "eq",
+            Instruction::AdjustRelativeBase(_) => "arb",
+            Instruction::Break => "hlt",
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<Parameter> {
+        match *self {
+            Instruction::Add(p1, p2, p3)
+            | Instruction::Multiply(p1, p2, p3)
+            | Instruction::LessThan(p1, p2, p3)
+            | Instruction::Equals(p1, p2, p3) => vec![p1, p2, p3],
+            Instruction::JumpIfTrue(p1, p2) | Instruction::JumpIfFalse(p1, p2) => vec![p1, p2],
+            Instruction::Input(p1)
+            | Instruction::Output(p1)
+            | Instruction::AdjustRelativeBase(p1) => {
+                vec![p1]
+            }
+            Instruction::Break => vec![],
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parameters = self
+            .parameters()
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>();
+        if parameters.is_empty() {
+            write!(f, "{}", self.mnemonic())
+        } else {
+            write!(f, "{} {}", self.mnemonic(), parameters.join(", "))
+        }
+    }
+}
+
+impl fmt::Display for ListingLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let raw = self
+            .raw
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        match &self.instruction {
+            Some(instruction) => write!(f, "{:>6}: {:<32} {}", self.address, raw, instruction),
+            None => write!(f, "{:>6}: {:<32} data {}", self.address, raw, self.raw[0]),
+        }
+    }
+}
+
+// Cells that don't decode, or whose instruction would run past the end of the image, are data
+pub fn disassemble(program: &[i128]) -> Vec<ListingLine> {
+    let computer = IntcodeComputer::new(program.to_vec());
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        let instruction = computer
+            .decode_at(address)
+            .ok()
+            .filter(|instruction| address + instruction.length() <= program.len());
+        let length = instruction.map_or(1, |instruction| instruction.length());
+        lines.push(ListingLine {
+            address,
+            raw: program[address..address + length].to_vec(),
+            instruction,
+        });
+        address += length;
+    }
+    lines
+}
+
+pub fn listing(program: &[i128]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntcodeError {
+    UnknownOpcode {
+        opcode: i128,
+        pointer: usize,
+    },
+    UnknownMode {
+        mode: i128,
+        parameter: u32,
+        pointer: usize,
+    },
+    NegativeAddress {
+        address: i128,
+        pointer: usize,
+    },
+    OutOfBounds {
+        address: i128,
+        pointer: usize,
+    },
+    WriteToImmediate {
+        pointer: usize,
+    },
+    // Arithmetic or an address computation past the range of its type
+    Overflow {
+        pointer: usize,
+    },
+    StepLimitExceeded {
+        limit: usize,
+        pointer: usize,
+    },
+    // Same memory, pointer and relative base seen twice with no input or output in between
+    RepeatedState {
+        pointer: usize,
+    },
+    // Machines of a network still waiting for input that no other machine will send
+    NetworkIdle,
+    MissingOutput {
+        machine: usize,
+    },
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntcodeError::UnknownOpcode { opcode, pointer } => {
+                write!(f, "Unknown opcode {} at address {}", opcode, pointer)
+            }
+            IntcodeError::UnknownMode {
+                mode,
+                parameter,
+                pointer,
+            } => write!(
+                f,
+                "Unknown mode {} for parameter {} at address {}",
+                mode, parameter, pointer
+            ),
+            IntcodeError::NegativeAddress { address, pointer } => write!(
+                f,
+                "Negative address {} used at address {}",
+                address, pointer
+            ),
+            IntcodeError::OutOfBounds { address, pointer } => write!(
+                f,
+                "Address {} out of bounds at address {}",
+                address, pointer
+            ),
+            IntcodeError::WriteToImmediate { pointer } => {
+                write!(f, "Write to immediate parameter at address {}", pointer)
+            }
+            IntcodeError::Overflow { pointer } => {
+                write!(f, "Arithmetic overflow at address {}", pointer)
+            }
+            IntcodeError::StepLimitExceeded { limit, pointer } => {
+                write!(f, "Step limit of {} exceeded at address {}", limit, pointer)
+            }
+            IntcodeError::RepeatedState { pointer } => write!(
+                f,
+                "Infinite loop without input or output at address {}",
+                pointer
+            ),
+            IntcodeError::NetworkIdle => {
+                write!(f, "Network stopped with machines waiting for input")
+            }
+            IntcodeError::MissingOutput { machine } => {
+                write!(f, "Machine {} halted without any output", machine)
+            }
+        }
+    }
+}
+
+impl Error for IntcodeError {}
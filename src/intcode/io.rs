@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::io::stdin;
+use std::sync::mpsc::{Receiver, Sender};
+
+// Input provider and output sink for a running IntcodeComputer.
+// Returning None from read pauses the machine with WaitForInput.
+pub trait IntcodeIo {
+    fn read(&mut self) -> Option<i128>;
+    fn write(&mut self, value: i128);
+}
+
+pub struct StdIo;
+
+impl IntcodeIo for StdIo {
+    // Asks again until a number is entered, stopping only once stdin is closed
+    fn read(&mut self) -> Option<i128> {
+        loop {
+            let mut buffer = String::new();
+            println!("Read input: ");
+            if stdin().read_line(&mut buffer).ok()? == 0 {
+                return None;
+            }
+            match buffer.trim().parse::<i128>() {
+                Ok(value) => return Some(value),
+                Err(_) => println!("Invalid input {}", buffer.trim()),
+            }
+        }
+    }
+
+    fn write(&mut self, value: i128) {
+        println!("Output: {}", value);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct QueueIo {
+    pub inputs: VecDeque<i128>,
+    pub outputs: Vec<i128>,
+}
+
+impl QueueIo {
+    pub fn new(inputs: Vec<i128>) -> QueueIo {
+        QueueIo {
+            inputs: inputs.into_iter().collect(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+impl IntcodeIo for QueueIo {
+    fn read(&mut self) -> Option<i128> {
+        self.inputs.pop_front()
+    }
+
+    fn write(&mut self, value: i128) {
+        self.outputs.push(value);
+    }
+}
+
+pub struct ClosureIo<R, W>
+where
+    R: FnMut() -> Option<i128>,
+    W: FnMut(i128),
+{
+    reader: R,
+    writer: W,
+}
+
+impl<R, W> ClosureIo<R, W>
+where
+    R: FnMut() -> Option<i128>,
+    W: FnMut(i128),
+{
+    pub fn new(reader: R, writer: W) -> ClosureIo<R, W> {
+        ClosureIo { reader, writer }
+    }
+}
+
+impl<R, W> IntcodeIo for ClosureIo<R, W>
+where
+    R: FnMut() -> Option<i128>,
+    W: FnMut(i128),
+{
+    fn read(&mut self) -> Option<i128> {
+        (self.reader)()
+    }
+
+    fn write(&mut self, value: i128) {
+        (self.writer)(value)
+    }
+}
+
+// Blocks on the receiver until a value arrives or every sender is dropped
+pub struct ChannelIo {
+    receiver: Receiver<i128>,
+    sender: Sender<i128>,
+}
+
+impl ChannelIo {
+    pub fn new(receiver: Receiver<i128>, sender: Sender<i128>) -> ChannelIo {
+        ChannelIo { receiver, sender }
+    }
+}
+
+impl IntcodeIo for ChannelIo {
+    fn read(&mut self) -> Option<i128> {
+        self.receiver.recv().ok()
+    }
+
+    fn write(&mut self, value: i128) {
+        // Nobody listening anymore is not an error for the machine
+        let _ = self.sender.send(value);
+    }
+}
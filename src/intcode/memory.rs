@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Addresses further than this past the end of the dense cells go to the sparse map
+const MAX_GROWTH: usize = 1 << 16;
+
+// Cloning is cheap: cells are shared until one of the copies writes to them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Memory {
+    cells: Arc<Vec<i128>>,
+    sparse: Arc<HashMap<usize, i128>>,
+}
+
+impl Memory {
+    pub fn new(program: Vec<i128>) -> Memory {
+        Memory {
+            cells: Arc::new(program),
+            sparse: Arc::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_parts(cells: Vec<i128>, sparse: Vec<(usize, i128)>) -> Memory {
+        let mut memory = Memory::new(cells);
+        for (address, value) in sparse {
+            memory.write(address, value);
+        }
+        memory
+    }
+
+    pub fn read(&self, address: usize) -> i128 {
+        match self.cells.get(address) {
+            Some(value) => *value,
+            None => *self.sparse.get(&address).unwrap_or(&0),
+        }
+    }
+
+    pub fn write(&mut self, address: usize, value: i128) {
+        if address < self.cells.len() {
+            Arc::make_mut(&mut self.cells)[address] = value;
+        } else if address < self.cells.len() + MAX_GROWTH {
+            let cells = Arc::make_mut(&mut self.cells);
+            cells.resize(address + 1, 0);
+            // Move any sparse cells now covered by the dense part
+            if self.sparse.keys().any(|pos| *pos < cells.len()) {
+                let len = cells.len();
+                Arc::make_mut(&mut self.sparse).retain(|pos, val| {
+                    if *pos < len {
+                        cells[*pos] = *val;
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+            cells[address] = value;
+        } else {
+            Arc::make_mut(&mut self.sparse).insert(address, value);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn dense(&self) -> &[i128] {
+        &self.cells
+    }
+
+    pub fn sparse_cells(&self) -> Vec<(usize, i128)> {
+        let mut cells: Vec<(usize, i128)> = self.sparse.iter().map(|(a, v)| (*a, *v)).collect();
+        cells.sort();
+        cells
+    }
+}
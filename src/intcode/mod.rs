@@ -0,0 +1,637 @@
+pub mod ascii;
+pub mod assembler;
+pub mod compiled;
+pub mod debugger;
+pub mod disassembler;
+mod error;
+pub mod io;
+mod memory;
+pub mod monitor;
+pub mod network;
+pub mod profiler;
+pub mod snapshot;
+pub mod symbolic;
+pub mod threaded;
+pub mod tracer;
+
+use crate::common::get_input;
+use compiled::DecodedProgram;
+pub use error::IntcodeError;
+use io::IntcodeIo;
+pub use memory::Memory;
+use monitor::CodeMonitor;
+use profiler::Profiler;
+use snapshot::Snapshot;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::ops::Range;
+use tracer::{TraceEvent, Tracer};
+
+pub struct IntcodeComputer {
+    memory: Memory,
+    pointer: usize,
+    relative_base: i128,
+    inputs: VecDeque<i128>,
+    tracer: Option<RefCell<Tracer>>,
+    decoded: Option<DecodedProgram>,
+    monitor: Option<CodeMonitor>,
+    profiler: Option<Profiler>,
+    steps: usize,
+    step_limit: Option<usize>,
+    loop_guard: Option<LoopGuard>,
+}
+
+// Brent's cycle detection over the states after taken jumps since the last input
+// or output: a single saved state, replaced after 1, 2, 4, ... further jumps
+struct LoopGuard {
+    saved: Option<Snapshot>,
+    power: usize,
+    jumps: usize,
+}
+
+impl LoopGuard {
+    fn new() -> LoopGuard {
+        LoopGuard {
+            saved: None,
+            power: 1,
+            jumps: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameter {
+    Immediate(i128),
+    Position(usize),
+    Relative(i128),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IntcodeReturn {
+    WaitForInput,
+    Output(i128),
+    Continue,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Add(Parameter, Parameter, Parameter),
+    Multiply(Parameter, Parameter, Parameter),
+    Input(Parameter),
+    Output(Parameter),
+    JumpIfTrue(Parameter, Parameter),
+    JumpIfFalse(Parameter, Parameter),
+    LessThan(Parameter, Parameter, Parameter),
+    Equals(Parameter, Parameter, Parameter),
+    AdjustRelativeBase(Parameter),
+    Break,
+}
+
+impl Instruction {
+    pub fn length(&self) -> usize {
+        match self {
+            Instruction::Add(..)
+            | Instruction::Multiply(..)
+            | Instruction::LessThan(..)
+            | Instruction::Equals(..) => 4,
+            Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => 3,
+            Instruction::Input(_) | Instruction::Output(_) | Instruction::AdjustRelativeBase(_) => {
+                2
+            }
+            Instruction::Break => 1,
+        }
+    }
+}
+
+pub fn load_program(filepath: &str) -> Vec<i128> {
+    get_input(filepath, ",")
+        .iter()
+        .map(|x| x.trim().parse::<i128>().unwrap())
+        .collect()
+}
+
+fn to_address(value: i128, pointer: usize) -> Result<usize, IntcodeError> {
+    if value < 0 {
+        return Err(IntcodeError::NegativeAddress {
+            address: value,
+            pointer,
+        });
+    }
+    value.try_into().map_err(|_| IntcodeError::OutOfBounds {
+        address: value,
+        pointer,
+    })
+}
+
+// Decodes the instruction at address from any view of memory
+pub fn decode<F: Fn(usize) -> i128>(read: F, address: usize) -> Result<Instruction, IntcodeError> {
+    let instruction_code = read(address);
+    let instruction = match instruction_code % 100 {
+        1 => Instruction::Add(
+            parameter_at(&read, address, instruction_code, 1)?,
+            parameter_at(&read, address, instruction_code, 2)?,
+            parameter_at(&read, address, instruction_code, 3)?,
+        ),
+        2 => Instruction::Multiply(
+            parameter_at(&read, address, instruction_code, 1)?,
+            parameter_at(&read, address, instruction_code, 2)?,
+            parameter_at(&read, address, instruction_code, 3)?,
+        ),
+        3 => Instruction::Input(parameter_at(&read, address, instruction_code, 1)?),
+        4 => Instruction::Output(parameter_at(&read, address, instruction_code, 1)?),
+        5 => Instruction::JumpIfTrue(
+            parameter_at(&read, address, instruction_code, 1)?,
+            parameter_at(&read, address, instruction_code, 2)?,
+        ),
+        6 => Instruction::JumpIfFalse(
+            parameter_at(&read, address, instruction_code, 1)?,
+            parameter_at(&read, address, instruction_code, 2)?,
+        ),
+        7 => Instruction::LessThan(
+            parameter_at(&read, address, instruction_code, 1)?,
+            parameter_at(&read, address, instruction_code, 2)?,
+            parameter_at(&read, address, instruction_code, 3)?,
+        ),
+        8 => Instruction::Equals(
+            parameter_at(&read, address, instruction_code, 1)?,
+            parameter_at(&read, address, instruction_code, 2)?,
+            parameter_at(&read, address, instruction_code, 3)?,
+        ),
+        9 => Instruction::AdjustRelativeBase(parameter_at(&read, address, instruction_code, 1)?),
+        99 => Instruction::Break,
+        _ => {
+            return Err(IntcodeError::UnknownOpcode {
+                opcode: instruction_code,
+                pointer: address,
+            })
+        }
+    };
+    Ok(instruction)
+}
+
+fn parameter_at<F: Fn(usize) -> i128>(
+    read: &F,
+    address: usize,
+    instruction_code: i128,
+    position: u32,
+) -> Result<Parameter, IntcodeError> {
+    let position_u: usize = position.try_into().unwrap();
+    let pos_multiplier: i128 = 10_i128.pow(position);
+    let mode: i128 = (instruction_code % (100 * pos_multiplier)) / (10 * pos_multiplier);
+    let value = read(
+        address
+            .checked_add(position_u)
+            .ok_or(IntcodeError::Overflow { pointer: address })?,
+    );
+    match mode {
+        0 => Ok(Parameter::Position(to_address(value, address)?)),
+        1 => Ok(Parameter::Immediate(value)),
+        2 => Ok(Parameter::Relative(value)),
+        _ => Err(IntcodeError::UnknownMode {
+            mode,
+            parameter: position,
+            pointer: address,
+        }),
+    }
+}
+
+impl IntcodeComputer {
+    pub fn new(program: Vec<i128>) -> IntcodeComputer {
+        IntcodeComputer {
+            memory: Memory::new(program),
+            pointer: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            tracer: None,
+            decoded: None,
+            monitor: None,
+            profiler: None,
+            steps: 0,
+            step_limit: None,
+            loop_guard: None,
+        }
+    }
+
+    pub fn from_snapshot(snapshot: Snapshot) -> IntcodeComputer {
+        IntcodeComputer {
+            memory: snapshot.memory,
+            pointer: snapshot.pointer,
+            relative_base: snapshot.relative_base,
+            inputs: snapshot.inputs,
+            tracer: None,
+            decoded: None,
+            monitor: None,
+            profiler: None,
+            steps: 0,
+            step_limit: None,
+            loop_guard: None,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.memory.clone(),
+            pointer: self.pointer,
+            relative_base: self.relative_base,
+            inputs: self.inputs.clone(),
+        }
+    }
+
+    // Keeps the tracer, if any, so a trace can follow a search across restores.
+    // The step count starts again so the step limit applies to each run.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.memory = snapshot.memory.clone();
+        self.pointer = snapshot.pointer;
+        self.relative_base = snapshot.relative_base;
+        self.inputs = snapshot.inputs.clone();
+        self.steps = 0;
+        if let Some(guard) = &mut self.loop_guard {
+            *guard = LoopGuard::new();
+        }
+        if self.decoded.is_some() {
+            self.precompile();
+        }
+    }
+
+    // Decodes the whole image once. While no tracer, monitor, profiler or loop
+    // detection is on, run executes the decoded instructions directly.
+    pub fn precompile(&mut self) {
+        self.decoded = Some(DecodedProgram::new(&self.memory));
+    }
+
+    pub fn push_input(&mut self, value: i128) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn push_inputs<I: IntoIterator<Item = i128>>(&mut self, values: I) {
+        self.inputs.extend(values);
+    }
+
+    pub fn pending_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn enable_trace(&mut self, tracer: Tracer) {
+        self.tracer = Some(RefCell::new(tracer));
+    }
+
+    pub fn take_trace(&mut self) -> Option<Tracer> {
+        self.tracer.take().map(RefCell::into_inner)
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // Executing more than limit instructions fails with StepLimitExceeded
+    pub fn set_step_limit(&mut self, limit: usize) {
+        self.step_limit = Some(limit);
+    }
+
+    // Fails with RepeatedState once the machine state after a taken jump comes back
+    // without any input or output in between. Keeps one copy of memory, compared
+    // against the state after every taken jump.
+    pub fn detect_loops(&mut self) {
+        self.loop_guard = Some(LoopGuard::new());
+    }
+
+    fn check_repeated_state(&mut self) -> Result<(), IntcodeError> {
+        let guard = match &self.loop_guard {
+            Some(guard) => guard,
+            None => return Ok(()),
+        };
+        if let Some(saved) = &guard.saved {
+            if saved.pointer == self.pointer
+                && saved.relative_base == self.relative_base
+                && saved.memory == self.memory
+            {
+                return Err(IntcodeError::RepeatedState {
+                    pointer: self.pointer,
+                });
+            }
+        }
+        let guard = self.loop_guard.as_mut().unwrap();
+        guard.jumps += 1;
+        if guard.jumps == guard.power {
+            guard.power *= 2;
+            guard.jumps = 0;
+            let state = self.snapshot();
+            self.loop_guard.as_mut().unwrap().saved = Some(state);
+        }
+        Ok(())
+    }
+
+    pub fn enable_code_monitor(&mut self) {
+        self.monitor = Some(CodeMonitor::new());
+    }
+
+    pub fn code_monitor(&self) -> Option<&CodeMonitor> {
+        self.monitor.as_ref()
+    }
+
+    pub fn enable_profiler(&mut self) {
+        self.profiler = Some(Profiler::new());
+    }
+
+    pub fn take_profile(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+
+    // Events are only built when tracing is on
+    fn trace<F: FnOnce() -> TraceEvent>(&self, event: F) {
+        if let Some(tracer) = &self.tracer {
+            tracer.borrow_mut().record(event());
+        }
+    }
+
+    pub fn read(&self, address: usize) -> i128 {
+        self.memory.read(address)
+    }
+
+    pub fn write(&mut self, address: usize, value: i128) {
+        self.memory.write(address, value);
+        if let Some(decoded) = &mut self.decoded {
+            decoded.invalidate(address);
+        }
+    }
+
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    pub fn relative_base(&self) -> i128 {
+        self.relative_base
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    pub fn memory_range(&self, range: Range<usize>) -> Vec<i128> {
+        range.map(|address| self.memory.read(address)).collect()
+    }
+
+    pub fn get_next_instruction(&self) -> Result<Instruction, IntcodeError> {
+        self.decode_at(self.pointer)
+    }
+
+    pub fn decode_at(&self, address: usize) -> Result<Instruction, IntcodeError> {
+        decode(|address| self.memory.read(address), address)
+    }
+
+    pub fn get_parameter(
+        &self,
+        instruction_code: i128,
+        position: u32,
+    ) -> Result<Parameter, IntcodeError> {
+        parameter_at(
+            &|address| self.memory.read(address),
+            self.pointer,
+            instruction_code,
+            position,
+        )
+    }
+
+    fn to_address(&self, value: i128) -> Result<usize, IntcodeError> {
+        to_address(value, self.pointer)
+    }
+
+    fn relative_address(&self, offset: i128) -> Result<usize, IntcodeError> {
+        let address = self.relative_base.checked_add(offset);
+        self.to_address(address.ok_or_else(|| self.overflow())?)
+    }
+
+    fn overflow(&self) -> IntcodeError {
+        IntcodeError::Overflow {
+            pointer: self.pointer,
+        }
+    }
+
+    pub fn fetch_data(&self, param: Parameter) -> Result<i128, IntcodeError> {
+        let value = match param {
+            Parameter::Immediate(value) => value,
+            Parameter::Position(pos) => self.memory.read(pos),
+            Parameter::Relative(offset) => self.memory.read(self.relative_address(offset)?),
+        };
+        self.trace(|| TraceEvent::Fetch {
+            parameter: param,
+            value,
+        });
+        Ok(value)
+    }
+
+    pub fn write_data(&mut self, param: Parameter, value: i128) -> Result<(), IntcodeError> {
+        let address = match param {
+            Parameter::Immediate(_) => {
+                return Err(IntcodeError::WriteToImmediate {
+                    pointer: self.pointer,
+                })
+            }
+            Parameter::Position(pos) => pos,
+            Parameter::Relative(offset) => self.relative_address(offset)?,
+        };
+        self.trace(|| TraceEvent::Write {
+            address,
+            old: self.memory.read(address),
+            new: value,
+        });
+        if let Some(monitor) = &mut self.monitor {
+            monitor.record_write(self.pointer, address, self.memory.read(address), value);
+        }
+        self.write(address, value);
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> Result<IntcodeReturn, IntcodeError> {
+        let instruction = self.get_next_instruction()?;
+        if let (Instruction::Input(_), None) = (instruction, self.inputs.front()) {
+            return Ok(IntcodeReturn::WaitForInput);
+        }
+        if let Some(limit) = self.step_limit {
+            if self.steps >= limit {
+                return Err(IntcodeError::StepLimitExceeded {
+                    limit,
+                    pointer: self.pointer,
+                });
+            }
+        }
+        self.steps += 1;
+        if let Instruction::Input(_) | Instruction::Output(_) = instruction {
+            if let Some(guard) = &mut self.loop_guard {
+                *guard = LoopGuard::new();
+            }
+        }
+        if let Some(tracer) = &self.tracer {
+            tracer
+                .borrow_mut()
+                .record_execute(self.pointer, self.relative_base, instruction);
+        }
+        if let Some(monitor) = &mut self.monitor {
+            monitor.record_execute(self.pointer, instruction.length());
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.record_execute(self.pointer, instruction);
+        }
+        let result = match instruction {
+            Instruction::Add(p1, p2, p3) => {
+                let sum = self.fetch_data(p1)?.checked_add(self.fetch_data(p2)?);
+                self.write_data(p3, sum.ok_or_else(|| self.overflow())?)?;
+                self.pointer += 4;
+                IntcodeReturn::Continue
+            }
+            Instruction::Multiply(p1, p2, p3) => {
+                let product = self.fetch_data(p1)?.checked_mul(self.fetch_data(p2)?);
+                self.write_data(p3, product.ok_or_else(|| self.overflow())?)?;
+                self.pointer += 4;
+                IntcodeReturn::Continue
+            }
+            Instruction::Input(p1) => match self.inputs.front() {
+                Some(value) => {
+                    self.write_data(p1, *value)?;
+                    self.inputs.pop_front();
+                    self.pointer += 2;
+                    IntcodeReturn::Continue
+                }
+                None => IntcodeReturn::WaitForInput,
+            },
+            Instruction::Output(p1) => {
+                let output = IntcodeReturn::Output(self.fetch_data(p1)?);
+                self.pointer += 2;
+                output
+            }
+            Instruction::JumpIfTrue(p1, p2) => {
+                if self.fetch_data(p1)? != 0 {
+                    self.pointer = self.to_address(self.fetch_data(p2)?)?;
+                    self.check_repeated_state()?;
+                } else {
+                    self.pointer += 3;
+                }
+                IntcodeReturn::Continue
+            }
+            Instruction::JumpIfFalse(p1, p2) => {
+                if self.fetch_data(p1)? == 0 {
+                    self.pointer = self.to_address(self.fetch_data(p2)?)?;
+                    self.check_repeated_state()?;
+                } else {
+                    self.pointer += 3;
+                }
+                IntcodeReturn::Continue
+            }
+            Instruction::LessThan(p1, p2, dest) => {
+                if self.fetch_data(p1)? < self.fetch_data(p2)? {
+                    self.write_data(dest, 1)?;
+                } else {
+                    self.write_data(dest, 0)?;
+                }
+                self.pointer += 4;
+                IntcodeReturn::Continue
+            }
+            Instruction::Equals(p1, p2, dest) => {
+                if self.fetch_data(p1)? == self.fetch_data(p2)? {
+                    self.write_data(dest, 1)?;
+                } else {
+                    self.write_data(dest, 0)?;
+                }
+                self.pointer += 4;
+                IntcodeReturn::Continue
+            }
+            Instruction::AdjustRelativeBase(p1) => {
+                let base = self.relative_base.checked_add(self.fetch_data(p1)?);
+                self.relative_base = base.ok_or_else(|| self.overflow())?;
+                self.pointer += 2;
+                IntcodeReturn::Continue
+            }
+            Instruction::Break => IntcodeReturn::End,
+        };
+        Ok(result)
+    }
+
+    fn instrumented(&self) -> bool {
+        self.tracer.is_some()
+            || self.monitor.is_some()
+            || self.profiler.is_some()
+            || self.loop_guard.is_some()
+    }
+
+    // Consumes queued inputs as the program asks for them, pausing when the queue runs out
+    pub fn run(&mut self) -> Result<IntcodeReturn, IntcodeError> {
+        if self.decoded.is_some() && !self.instrumented() {
+            return self.run_decoded();
+        }
+        loop {
+            let result = self.step()?;
+            if let IntcodeReturn::Continue = result {
+                continue;
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    // Runs until the program halts or the io has no more input to give
+    pub fn run_with_io<T: IntcodeIo>(&mut self, io: &mut T) -> Result<IntcodeReturn, IntcodeError> {
+        loop {
+            match self.run()? {
+                IntcodeReturn::Output(value) => io.write(value),
+                IntcodeReturn::WaitForInput => match io.read() {
+                    Some(value) => self.push_input(value),
+                    None => return Ok(IntcodeReturn::WaitForInput),
+                },
+                result => return Ok(result),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loop_guard_reports_a_jump_to_itself() {
+        let mut computer = IntcodeComputer::new(vec![1105, 1, 0]);
+        computer.detect_loops();
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::RepeatedState { pointer: 0 })
+        );
+    }
+
+    #[test]
+    fn loop_guard_reports_a_counter_that_wraps_around() {
+        // Counts to 1000, resets to 0 and starts over
+        let mut computer = IntcodeComputer::new(vec![
+            1001, 20, 1, 20, 1008, 20, 1000, 21, 1006, 21, 0, 1101, 0, 0, 20, 1105, 1, 0, 99, 0, 0,
+            0,
+        ]);
+        computer.detect_loops();
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::RepeatedState { pointer: 0 })
+        );
+        assert!(computer.steps() < 20_000);
+    }
+
+    #[test]
+    fn loop_guard_lets_a_long_loop_finish() {
+        // Counts down from 200000 without any output, in a 2000 cell image
+        let mut program = vec![1001, 10, -1, 10, 1005, 10, 0, 4, 10, 99, 200_000];
+        program.resize(2000, 0);
+        let mut computer = IntcodeComputer::new(program);
+        computer.detect_loops();
+        computer.set_step_limit(1_000_000);
+        assert_eq!(computer.run(), Ok(IntcodeReturn::Output(0)));
+        assert_eq!(computer.run(), Ok(IntcodeReturn::End));
+    }
+
+    #[test]
+    fn loop_guard_starts_over_after_output() {
+        let mut computer = IntcodeComputer::new(vec![104, 1, 1105, 1, 0]);
+        computer.detect_loops();
+        for _ in 0..10 {
+            assert_eq!(computer.run(), Ok(IntcodeReturn::Output(1)));
+        }
+    }
+}
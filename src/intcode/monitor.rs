@@ -0,0 +1,85 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeWrite {
+    pub address: usize,
+    pub pointer: usize,
+    pub old: i128,
+    pub new: i128,
+}
+
+impl fmt::Display for CodeWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} -> {} by the instruction at {}",
+            self.address, self.old, self.new, self.pointer
+        )
+    }
+}
+
+// Remembers which cells were executed, as an opcode or a parameter, and which were
+// written. A write counts as a code write if it lands on a cell already executed,
+// or if the cell is executed afterwards.
+#[derive(Debug, Default)]
+pub struct CodeMonitor {
+    executed: HashSet<usize>,
+    written: HashSet<usize>,
+    // Last write to each cell not executed yet
+    pending: HashMap<usize, CodeWrite>,
+    code_writes: Vec<CodeWrite>,
+}
+
+fn sorted(addresses: &HashSet<usize>) -> Vec<usize> {
+    let mut addresses: Vec<usize> = addresses.iter().cloned().collect();
+    addresses.sort();
+    addresses
+}
+
+impl CodeMonitor {
+    pub fn new() -> CodeMonitor {
+        CodeMonitor::default()
+    }
+
+    pub fn record_execute(&mut self, pointer: usize, length: usize) {
+        for address in pointer..pointer + length {
+            if self.executed.insert(address) {
+                if let Some(write) = self.pending.remove(&address) {
+                    self.code_writes.push(write);
+                }
+            }
+        }
+    }
+
+    pub fn record_write(&mut self, pointer: usize, address: usize, old: i128, new: i128) {
+        self.written.insert(address);
+        let write = CodeWrite {
+            address,
+            pointer,
+            old,
+            new,
+        };
+        if self.executed.contains(&address) {
+            self.code_writes.push(write);
+        } else {
+            self.pending.insert(address, write);
+        }
+    }
+
+    pub fn executed(&self) -> Vec<usize> {
+        sorted(&self.executed)
+    }
+
+    pub fn written(&self) -> Vec<usize> {
+        sorted(&self.written)
+    }
+
+    pub fn code_writes(&self) -> &[CodeWrite] {
+        &self.code_writes
+    }
+
+    pub fn is_self_modifying(&self) -> bool {
+        !self.code_writes.is_empty()
+    }
+}
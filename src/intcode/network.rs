@@ -0,0 +1,184 @@
+use super::{IntcodeComputer, IntcodeError, IntcodeReturn};
+
+// Steps a machine may run before the scheduler moves on to the next one
+const SLICE_STEPS: usize = 10_000;
+
+pub enum Wiring {
+    // Every output of the first machine is queued as input of the second one
+    Direct(Vec<(usize, usize)>),
+    // Outputs are grouped in packets whose first value is the destination address.
+    // A machine waiting on an empty queue is given empty_input instead (-1 in day 23).
+    Packets {
+        packet_size: usize,
+        empty_input: i128,
+    },
+}
+
+pub fn chain(machines: usize) -> Vec<(usize, usize)> {
+    (1..machines).map(|i| (i - 1, i)).collect()
+}
+
+pub fn ring(machines: usize) -> Vec<(usize, usize)> {
+    let mut edges = chain(machines);
+    if machines > 0 {
+        edges.push((machines - 1, 0));
+    }
+    edges
+}
+
+#[derive(Debug, PartialEq)]
+pub enum NetworkEvent {
+    // Every machine halted
+    Halted,
+    // Two full rounds went by without any machine sending or receiving a value
+    Idle,
+    // A packet addressed outside the network, like the NAT at 255
+    External {
+        from: usize,
+        address: i128,
+        payload: Vec<i128>,
+    },
+}
+
+pub struct Network {
+    computers: Vec<IntcodeComputer>,
+    wiring: Wiring,
+    halted: Vec<bool>,
+    outputs: Vec<Vec<i128>>,
+    packets: Vec<Vec<i128>>,
+    next: usize,
+    idle_slices: usize,
+}
+
+impl Network {
+    pub fn new(computers: Vec<IntcodeComputer>, wiring: Wiring) -> Network {
+        let machines = computers.len();
+        Network {
+            computers,
+            wiring,
+            halted: vec![false; machines],
+            outputs: vec![Vec::new(); machines],
+            packets: vec![Vec::new(); machines],
+            next: 0,
+            idle_slices: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.computers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.computers.is_empty()
+    }
+
+    pub fn send(&mut self, machine: usize, values: &[i128]) {
+        self.computers[machine].push_inputs(values.iter().cloned());
+        self.idle_slices = 0;
+    }
+
+    pub fn outputs(&self, machine: usize) -> &[i128] {
+        &self.outputs[machine]
+    }
+
+    pub fn computer(&self, machine: usize) -> &IntcodeComputer {
+        &self.computers[machine]
+    }
+
+    fn route(&mut self, from: usize, value: i128) -> Option<NetworkEvent> {
+        self.outputs[from].push(value);
+        match self.wiring {
+            Wiring::Direct(ref edges) => {
+                for (_, to) in edges.iter().filter(|(source, _)| *source == from) {
+                    self.computers[*to].push_input(value);
+                }
+                None
+            }
+            Wiring::Packets { packet_size, .. } => {
+                self.packets[from].push(value);
+                if self.packets[from].len() < packet_size {
+                    return None;
+                }
+                let mut payload: Vec<i128> = self.packets[from].drain(..).collect();
+                let address = payload.remove(0);
+                if address >= 0 && (address as usize) < self.computers.len() {
+                    self.computers[address as usize].push_inputs(payload);
+                    None
+                } else {
+                    Some(NetworkEvent::External {
+                        from,
+                        address,
+                        payload,
+                    })
+                }
+            }
+        }
+    }
+
+    // Runs one machine until it blocks, halts, uses up its slice or sends an external packet
+    fn run_slice(&mut self, machine: usize) -> Result<(bool, Option<NetworkEvent>), IntcodeError> {
+        let mut active = self.computers[machine].pending_inputs() > 0;
+        let mut given_empty_input = false;
+        let mut blocked = false;
+        for _ in 0..SLICE_STEPS {
+            match self.computers[machine].step()? {
+                IntcodeReturn::Continue => {}
+                IntcodeReturn::Output(value) => {
+                    active = true;
+                    if let Some(event) = self.route(machine, value) {
+                        return Ok((active, Some(event)));
+                    }
+                }
+                IntcodeReturn::WaitForInput => match self.wiring {
+                    Wiring::Packets { empty_input, .. } if !given_empty_input => {
+                        self.computers[machine].push_input(empty_input);
+                        given_empty_input = true;
+                    }
+                    _ => {
+                        blocked = true;
+                        break;
+                    }
+                },
+                IntcodeReturn::End => {
+                    self.halted[machine] = true;
+                    blocked = true;
+                    break;
+                }
+            }
+        }
+        // Still computing when the slice ran out
+        Ok((active || !blocked, None))
+    }
+
+    // Round-robin over the machines until something the caller has to handle happens.
+    // Calling run again resumes where the previous call stopped.
+    pub fn run(&mut self) -> Result<NetworkEvent, IntcodeError> {
+        let machines = self.computers.len();
+        loop {
+            if self.halted.iter().all(|halted| *halted) {
+                return Ok(NetworkEvent::Halted);
+            }
+            if self.idle_slices >= 2 * machines {
+                self.idle_slices = 0;
+                return Ok(NetworkEvent::Idle);
+            }
+
+            let machine = self.next;
+            self.next = (self.next + 1) % machines;
+            if self.halted[machine] {
+                self.idle_slices += 1;
+                continue;
+            }
+
+            let (active, event) = self.run_slice(machine)?;
+            if active {
+                self.idle_slices = 0;
+            } else {
+                self.idle_slices += 1;
+            }
+            if let Some(event) = event {
+                return Ok(event);
+            }
+        }
+    }
+}
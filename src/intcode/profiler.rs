@@ -0,0 +1,142 @@
+use super::disassembler::{disassemble, ListingLine};
+use super::{decode, Instruction};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IoKind {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IoGap {
+    pub kind: IoKind,
+    pub step: usize,
+    // Instructions executed since the previous input or output
+    pub steps: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Profiler {
+    steps: usize,
+    per_address: HashMap<usize, usize>,
+    per_opcode: HashMap<&'static str, usize>,
+    io_gaps: Vec<IoGap>,
+    last_io: usize,
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    100.0 * count as f64 / total.max(1) as f64
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    pub fn record_execute(&mut self, pointer: usize, instruction: Instruction) {
+        self.steps += 1;
+        *self.per_address.entry(pointer).or_insert(0) += 1;
+        *self.per_opcode.entry(instruction.mnemonic()).or_insert(0) += 1;
+        let kind = match instruction {
+            Instruction::Input(_) => IoKind::Input,
+            Instruction::Output(_) => IoKind::Output,
+            _ => return,
+        };
+        self.io_gaps.push(IoGap {
+            kind,
+            step: self.steps,
+            steps: self.steps - self.last_io,
+        });
+        self.last_io = self.steps;
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn count_at(&self, address: usize) -> usize {
+        *self.per_address.get(&address).unwrap_or(&0)
+    }
+
+    // Most executed opcodes first
+    pub fn opcodes(&self) -> Vec<(&'static str, usize)> {
+        let mut opcodes: Vec<(&'static str, usize)> =
+            self.per_opcode.iter().map(|(m, c)| (*m, *c)).collect();
+        opcodes.sort_by_key(|(mnemonic, count)| (std::cmp::Reverse(*count), *mnemonic));
+        opcodes
+    }
+
+    // Most executed addresses first
+    pub fn hotspots(&self) -> Vec<(usize, usize)> {
+        let mut hotspots: Vec<(usize, usize)> =
+            self.per_address.iter().map(|(a, c)| (*a, *c)).collect();
+        hotspots.sort_by_key(|(address, count)| (std::cmp::Reverse(*count), *address));
+        hotspots
+    }
+
+    pub fn io_gaps(&self) -> &[IoGap] {
+        &self.io_gaps
+    }
+
+    // Summary, opcode counts and the top hotspots, each shown with its line from
+    // the disassembly of image. Addresses the static listing doesn't start an
+    // instruction at, like code written at runtime, are decoded on their own.
+    pub fn report(&self, image: &[i128], top: usize) -> String {
+        let mut lines = vec![format!("Steps: {}", self.steps)];
+        let gaps: Vec<usize> = self.io_gaps.iter().map(|gap| gap.steps).collect();
+        if let (Some(min), Some(max)) = (gaps.iter().min(), gaps.iter().max()) {
+            lines.push(format!(
+                "I/O events: {}, steps between them: min {} avg {:.1} max {}",
+                gaps.len(),
+                min,
+                gaps.iter().sum::<usize>() as f64 / gaps.len() as f64,
+                max
+            ));
+        }
+
+        lines.push(String::from("Opcodes:"));
+        for (mnemonic, count) in self.opcodes() {
+            lines.push(format!(
+                "  {:<4} {:>12} {:>6.2}%",
+                mnemonic,
+                count,
+                percent(count, self.steps)
+            ));
+        }
+
+        let listing: HashMap<usize, ListingLine> = disassemble(image)
+            .into_iter()
+            .map(|line| (line.address, line))
+            .collect();
+        lines.push(String::from("Hotspots:"));
+        for (address, count) in self.hotspots().into_iter().take(top) {
+            let line = match listing.get(&address) {
+                Some(line) => line.to_string(),
+                None => {
+                    let read = |a: usize| *image.get(a).unwrap_or(&0);
+                    match decode(read, address) {
+                        Ok(instruction) => format!("{:>6}: {}", address, instruction),
+                        Err(_) => format!("{:>6}: ?", address),
+                    }
+                }
+            };
+            lines.push(format!(
+                "  {:>12} {:>6.2}% {}",
+                count,
+                percent(count, self.steps),
+                line
+            ));
+        }
+        lines.join("\n")
+    }
+
+    // Full disassembly of image with the execution count of every instruction
+    pub fn annotated_listing(&self, image: &[i128]) -> String {
+        disassemble(image)
+            .iter()
+            .map(|line| format!("{:>12} {}", self.count_at(line.address), line))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
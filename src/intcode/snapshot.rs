@@ -0,0 +1,117 @@
+use super::Memory;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Write};
+use std::str::FromStr;
+
+// Machine state without the tracer. Cloning shares memory until either copy writes to it.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub memory: Memory,
+    pub pointer: usize,
+    pub relative_base: i128,
+    pub inputs: VecDeque<i128>,
+}
+
+fn join<T: ToString, I: Iterator<Item = T>>(values: I) -> String {
+    values
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_list<T: FromStr>(text: &str) -> Result<Vec<T>, Error> {
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    text.split(',')
+        .map(|v| {
+            v.trim()
+                .parse::<T>()
+                .map_err(|_| invalid(format!("Invalid value '{}'", v)))
+        })
+        .collect()
+}
+
+impl Snapshot {
+    // One "key value" line per field, lists comma separated
+    pub fn save(&self, filepath: &str) -> std::io::Result<()> {
+        let mut file = File::create(filepath)?;
+        writeln!(file, "pointer {}", self.pointer)?;
+        writeln!(file, "relative_base {}", self.relative_base)?;
+        writeln!(file, "inputs {}", join(self.inputs.iter()))?;
+        writeln!(file, "memory {}", join(self.memory.dense().iter()))?;
+        writeln!(
+            file,
+            "sparse {}",
+            join(
+                self.memory
+                    .sparse_cells()
+                    .iter()
+                    .map(|(address, value)| format!("{}={}", address, value))
+            )
+        )?;
+        Ok(())
+    }
+
+    pub fn load(filepath: &str) -> std::io::Result<Snapshot> {
+        let mut pointer = None;
+        let mut relative_base = None;
+        let mut inputs = VecDeque::new();
+        let mut cells = None;
+        let mut sparse = Vec::new();
+
+        for line in BufReader::new(File::open(filepath)?).lines() {
+            let line = line?;
+            let (key, value) = match line.find(' ') {
+                Some(index) => (&line[..index], line[index + 1..].trim()),
+                None => (line.trim(), ""),
+            };
+            match key {
+                "pointer" => {
+                    pointer = Some(
+                        value
+                            .parse::<usize>()
+                            .map_err(|_| invalid(format!("Invalid pointer '{}'", value)))?,
+                    )
+                }
+                "relative_base" => {
+                    relative_base = Some(
+                        value
+                            .parse::<i128>()
+                            .map_err(|_| invalid(format!("Invalid relative base '{}'", value)))?,
+                    )
+                }
+                "inputs" => inputs = parse_list::<i128>(value)?.into_iter().collect(),
+                "memory" => cells = Some(parse_list::<i128>(value)?),
+                "sparse" => {
+                    for cell in parse_list::<String>(value)? {
+                        let mut parts = cell.splitn(2, '=');
+                        let address = parts.next().and_then(|a| a.parse::<usize>().ok());
+                        let value = parts.next().and_then(|v| v.parse::<i128>().ok());
+                        match (address, value) {
+                            (Some(address), Some(value)) => sparse.push((address, value)),
+                            _ => return Err(invalid(format!("Invalid sparse cell '{}'", cell))),
+                        }
+                    }
+                }
+                "" => {}
+                _ => return Err(invalid(format!("Unknown field '{}'", key))),
+            }
+        }
+
+        match (pointer, relative_base, cells) {
+            (Some(pointer), Some(relative_base), Some(cells)) => Ok(Snapshot {
+                memory: Memory::from_parts(cells, sparse),
+                pointer,
+                relative_base,
+                inputs,
+            }),
+            _ => Err(invalid(String::from("Incomplete snapshot"))),
+        }
+    }
+}
@@ -0,0 +1,465 @@
+use super::snapshot::Snapshot;
+use super::{decode, Instruction, IntcodeError, Parameter};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+// Polynomial over named inputs. Each monomial is the sorted list of its variables,
+// so noun*noun*verb is ["noun", "noun", "verb"] and the constant term is [].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Expr {
+    terms: BTreeMap<Vec<String>, i128>,
+}
+
+impl Expr {
+    pub fn constant(value: i128) -> Expr {
+        let mut terms = BTreeMap::new();
+        if value != 0 {
+            terms.insert(Vec::new(), value);
+        }
+        Expr { terms }
+    }
+
+    pub fn variable(name: &str) -> Expr {
+        let mut terms = BTreeMap::new();
+        terms.insert(vec![name.to_string()], 1);
+        Expr { terms }
+    }
+
+    pub fn as_constant(&self) -> Option<i128> {
+        match self.terms.iter().next() {
+            None => Some(0),
+            Some((monomial, value)) if monomial.is_empty() && self.terms.len() == 1 => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn degree(&self) -> usize {
+        self.terms
+            .keys()
+            .map(|monomial| monomial.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn variables(&self) -> Vec<&str> {
+        let mut variables: Vec<&str> = self
+            .terms
+            .keys()
+            .flatten()
+            .map(|name| name.as_str())
+            .collect();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    // Coefficient of each given variable plus the constant term, if the expression
+    // is linear and only uses those variables
+    pub fn linear_coefficients(&self, variables: &[&str]) -> Option<(Vec<i128>, i128)> {
+        if self.degree() > 1 {
+            return None;
+        }
+        let mut coefficients = vec![0; variables.len()];
+        let mut constant = 0;
+        for (monomial, value) in &self.terms {
+            match monomial.first() {
+                None => constant = *value,
+                Some(name) => {
+                    let index = variables.iter().position(|v| v == name)?;
+                    coefficients[index] = *value;
+                }
+            }
+        }
+        Some((coefficients, constant))
+    }
+
+    // None if a variable has no value
+    pub fn evaluate(&self, values: &HashMap<&str, i128>) -> Option<i128> {
+        self.terms.iter().try_fold(0, |total, (monomial, value)| {
+            let product = monomial.iter().try_fold(*value, |product, name| {
+                values.get(name.as_str()).map(|v| product * v)
+            })?;
+            Some(total + product)
+        })
+    }
+
+    fn add_term(&mut self, monomial: Vec<String>, value: i128) {
+        let coefficient = self.terms.entry(monomial.clone()).or_insert(0);
+        *coefficient += value;
+        if *coefficient == 0 {
+            self.terms.remove(&monomial);
+        }
+    }
+}
+
+impl Add for Expr {
+    type Output = Expr;
+
+    fn add(mut self, other: Expr) -> Expr {
+        for (monomial, value) in other.terms {
+            self.add_term(monomial, value);
+        }
+        self
+    }
+}
+
+impl Sub for Expr {
+    type Output = Expr;
+
+    fn sub(self, other: Expr) -> Expr {
+        self + other * Expr::constant(-1)
+    }
+}
+
+impl Mul for Expr {
+    type Output = Expr;
+
+    fn mul(self, other: Expr) -> Expr {
+        let mut result = Expr::default();
+        for (left, a) in &self.terms {
+            for (right, b) in &other.terms {
+                let mut monomial: Vec<String> = left.iter().chain(right).cloned().collect();
+                monomial.sort();
+                result.add_term(monomial, a * b);
+            }
+        }
+        result
+    }
+}
+
+fn monomial_string(monomial: &[String]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < monomial.len() {
+        let power = monomial[i..]
+            .iter()
+            .take_while(|name| **name == monomial[i])
+            .count();
+        if power == 1 {
+            parts.push(monomial[i].clone());
+        } else {
+            parts.push(format!("{}^{}", monomial[i], power));
+        }
+        i += power;
+    }
+    parts.join("*")
+}
+
+// Highest degree first, constant last: 460800*noun + verb + 2
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut terms: Vec<(&Vec<String>, &i128)> = self.terms.iter().collect();
+        if terms.is_empty() {
+            return write!(f, "0");
+        }
+        terms.sort_by_key(|(monomial, _)| std::cmp::Reverse(monomial.len()));
+        for (index, (monomial, value)) in terms.into_iter().enumerate() {
+            let magnitude = value.abs();
+            match (index, *value < 0) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            if monomial.is_empty() {
+                write!(f, "{}", magnitude)?;
+            } else if magnitude == 1 {
+                write!(f, "{}", monomial_string(monomial))?;
+            } else {
+                write!(f, "{}*{}", magnitude, monomial_string(monomial))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolicError {
+    Machine(IntcodeError),
+    // The opcode of the instruction depends on an input
+    SymbolicInstruction { pointer: usize },
+    // A write destination, jump target or relative base depends on an input
+    SymbolicAddress { pointer: usize },
+    // A comparison or jump condition can't be decided without knowing the inputs
+    SymbolicBranch { pointer: usize },
+    StepLimitExceeded { limit: usize, pointer: usize },
+}
+
+impl fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SymbolicError::Machine(error) => write!(f, "{}", error),
+            SymbolicError::SymbolicInstruction { pointer } => {
+                write!(f, "Instruction at {} depends on the inputs", pointer)
+            }
+            SymbolicError::SymbolicAddress { pointer } => {
+                write!(f, "Address used at {} depends on the inputs", pointer)
+            }
+            SymbolicError::SymbolicBranch { pointer } => {
+                write!(f, "Condition at {} depends on the inputs", pointer)
+            }
+            SymbolicError::StepLimitExceeded { limit, pointer } => {
+                write!(f, "Step limit of {} exceeded at address {}", limit, pointer)
+            }
+        }
+    }
+}
+
+impl Error for SymbolicError {}
+
+impl From<IntcodeError> for SymbolicError {
+    fn from(error: IntcodeError) -> SymbolicError {
+        SymbolicError::Machine(error)
+    }
+}
+
+// Runs a program with memory cells holding expressions instead of numbers.
+// Arithmetic is carried out on the expressions, but control flow and addressing
+// must stay concrete, so only straight-line dependencies on the inputs are followed.
+pub struct SymbolicComputer {
+    memory: HashMap<usize, Expr>,
+    pointer: usize,
+    relative_base: i128,
+    inputs: VecDeque<Expr>,
+    outputs: Vec<Expr>,
+    fresh_inputs: usize,
+    steps: usize,
+    step_limit: Option<usize>,
+}
+
+impl SymbolicComputer {
+    pub fn new(program: &[i128]) -> SymbolicComputer {
+        SymbolicComputer {
+            memory: program
+                .iter()
+                .enumerate()
+                .map(|(address, value)| (address, Expr::constant(*value)))
+                .collect(),
+            pointer: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            outputs: Vec::new(),
+            fresh_inputs: 0,
+            steps: 0,
+            step_limit: None,
+        }
+    }
+
+    pub fn from_snapshot(snapshot: &Snapshot) -> SymbolicComputer {
+        let mut computer = SymbolicComputer::new(snapshot.memory.dense());
+        for (address, value) in snapshot.memory.sparse_cells() {
+            computer.write(address, Expr::constant(value));
+        }
+        computer.pointer = snapshot.pointer;
+        computer.relative_base = snapshot.relative_base;
+        computer.inputs = snapshot
+            .inputs
+            .iter()
+            .cloned()
+            .map(Expr::constant)
+            .collect();
+        computer
+    }
+
+    // Replaces the value at address with a named input
+    pub fn bind(&mut self, address: usize, name: &str) {
+        self.write(address, Expr::variable(name));
+    }
+
+    pub fn push_input(&mut self, value: Expr) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn read(&self, address: usize) -> Expr {
+        self.memory.get(&address).cloned().unwrap_or_default()
+    }
+
+    pub fn write(&mut self, address: usize, value: Expr) {
+        self.memory.insert(address, value);
+    }
+
+    pub fn outputs(&self) -> &[Expr] {
+        &self.outputs
+    }
+
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // Executing more than limit instructions fails with StepLimitExceeded
+    pub fn set_step_limit(&mut self, limit: usize) {
+        self.step_limit = Some(limit);
+    }
+
+    fn concrete(&self, address: usize) -> Option<i128> {
+        self.memory.get(&address).map_or(Some(0), Expr::as_constant)
+    }
+
+    // Only the opcode has to be known. Parameter cells that depend on the inputs
+    // decode as 0 here and are looked at again when they are used.
+    fn next_instruction(&self) -> Result<Instruction, SymbolicError> {
+        if self.concrete(self.pointer).is_none() {
+            return Err(SymbolicError::SymbolicInstruction {
+                pointer: self.pointer,
+            });
+        }
+        let instruction = decode(|address| self.concrete(address).unwrap_or(0), self.pointer)?;
+        Ok(instruction)
+    }
+
+    // Expression held by the parameter cell itself, if it isn't a known number
+    fn symbolic_cell(&self, position: usize) -> Option<Expr> {
+        let cell = self.read(self.pointer + position);
+        match cell.as_constant() {
+            Some(_) => None,
+            None => Some(cell),
+        }
+    }
+
+    fn address(&self, value: i128) -> Result<usize, SymbolicError> {
+        super::to_address(value, self.pointer).map_err(SymbolicError::from)
+    }
+
+    // Reading through an address that depends on the inputs gives an opaque value
+    // named after the address, like [noun]
+    fn fetch(&self, position: usize, param: Parameter) -> Result<Expr, SymbolicError> {
+        if let Some(cell) = self.symbolic_cell(position) {
+            return Ok(match param {
+                Parameter::Immediate(_) => cell,
+                Parameter::Position(_) => Expr::variable(&format!("[{}]", cell)),
+                Parameter::Relative(_) => {
+                    Expr::variable(&format!("[{}]", Expr::constant(self.relative_base) + cell))
+                }
+            });
+        }
+        match param {
+            Parameter::Immediate(value) => Ok(Expr::constant(value)),
+            Parameter::Position(address) => Ok(self.read(address)),
+            Parameter::Relative(offset) => {
+                Ok(self.read(self.address(self.relative_base + offset)?))
+            }
+        }
+    }
+
+    fn concrete_value(&self, position: usize, param: Parameter) -> Result<i128, SymbolicError> {
+        self.fetch(position, param)?
+            .as_constant()
+            .ok_or(SymbolicError::SymbolicAddress {
+                pointer: self.pointer,
+            })
+    }
+
+    fn store(
+        &mut self,
+        position: usize,
+        param: Parameter,
+        value: Expr,
+    ) -> Result<(), SymbolicError> {
+        if self.symbolic_cell(position).is_some() {
+            return Err(SymbolicError::SymbolicAddress {
+                pointer: self.pointer,
+            });
+        }
+        let address = match param {
+            Parameter::Immediate(_) => {
+                return Err(IntcodeError::WriteToImmediate {
+                    pointer: self.pointer,
+                }
+                .into())
+            }
+            Parameter::Position(address) => address,
+            Parameter::Relative(offset) => self.address(self.relative_base + offset)?,
+        };
+        self.write(address, value);
+        Ok(())
+    }
+
+    // Difference of the operands, which has to be a known number
+    fn compare(&self, p1: Parameter, p2: Parameter) -> Result<i128, SymbolicError> {
+        (self.fetch(1, p1)? - self.fetch(2, p2)?)
+            .as_constant()
+            .ok_or(SymbolicError::SymbolicBranch {
+                pointer: self.pointer,
+            })
+    }
+
+    fn condition(&self, param: Parameter) -> Result<bool, SymbolicError> {
+        self.fetch(1, param)?
+            .as_constant()
+            .map(|value| value != 0)
+            .ok_or(SymbolicError::SymbolicBranch {
+                pointer: self.pointer,
+            })
+    }
+
+    // Returns false once the program halts. Input with an empty queue reads a new
+    // variable named input0, input1...
+    pub fn step(&mut self) -> Result<bool, SymbolicError> {
+        let instruction = self.next_instruction()?;
+        if let Some(limit) = self.step_limit {
+            if self.steps >= limit {
+                return Err(SymbolicError::StepLimitExceeded {
+                    limit,
+                    pointer: self.pointer,
+                });
+            }
+        }
+        self.steps += 1;
+        match instruction {
+            Instruction::Add(p1, p2, p3) => {
+                let value = self.fetch(1, p1)? + self.fetch(2, p2)?;
+                self.store(3, p3, value)?;
+            }
+            Instruction::Multiply(p1, p2, p3) => {
+                let value = self.fetch(1, p1)? * self.fetch(2, p2)?;
+                self.store(3, p3, value)?;
+            }
+            Instruction::Input(p1) => {
+                let value = match self.inputs.pop_front() {
+                    Some(value) => value,
+                    None => {
+                        self.fresh_inputs += 1;
+                        Expr::variable(&format!("input{}", self.fresh_inputs - 1))
+                    }
+                };
+                self.store(1, p1, value)?;
+            }
+            Instruction::Output(p1) => {
+                let value = self.fetch(1, p1)?;
+                self.outputs.push(value);
+            }
+            Instruction::JumpIfTrue(p1, p2) | Instruction::JumpIfFalse(p1, p2) => {
+                let jump_if = matches!(instruction, Instruction::JumpIfTrue(..));
+                if self.condition(p1)? == jump_if {
+                    self.pointer = self.address(self.concrete_value(2, p2)?)?;
+                    return Ok(true);
+                }
+            }
+            Instruction::LessThan(p1, p2, dest) => {
+                let value = Expr::constant((self.compare(p1, p2)? < 0) as i128);
+                self.store(3, dest, value)?;
+            }
+            Instruction::Equals(p1, p2, dest) => {
+                let value = Expr::constant((self.compare(p1, p2)? == 0) as i128);
+                self.store(3, dest, value)?;
+            }
+            Instruction::AdjustRelativeBase(p1) => {
+                self.relative_base += self.concrete_value(1, p1)?;
+            }
+            Instruction::Break => return Ok(false),
+        }
+        self.pointer += instruction.length();
+        Ok(true)
+    }
+
+    pub fn run(&mut self) -> Result<(), SymbolicError> {
+        while self.step()? {}
+        Ok(())
+    }
+}
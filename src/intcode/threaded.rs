@@ -0,0 +1,167 @@
+use super::io::IntcodeIo;
+use super::{IntcodeComputer, IntcodeError};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// How often a waiting machine checks whether the whole network is stuck
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+// Shared by every machine to tell a slow network from one where all machines wait on each other
+#[derive(Default)]
+struct Activity {
+    running: usize,
+    waiting: usize,
+    // Values sent but not read yet
+    in_flight: usize,
+    stalled: bool,
+}
+
+// Reads from the machine's own channel and copies every output to each connected machine
+struct MachineIo {
+    receiver: Receiver<i128>,
+    senders: Vec<Sender<i128>>,
+    outputs: Vec<i128>,
+    activity: Arc<Mutex<Activity>>,
+}
+
+impl IntcodeIo for MachineIo {
+    fn read(&mut self) -> Option<i128> {
+        self.activity.lock().unwrap().waiting += 1;
+        let value = loop {
+            match self.receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(value) => break Some(value),
+                Err(RecvTimeoutError::Disconnected) => break None,
+                Err(RecvTimeoutError::Timeout) => {
+                    let mut activity = self.activity.lock().unwrap();
+                    if activity.stalled
+                        || (activity.waiting == activity.running && activity.in_flight == 0)
+                    {
+                        activity.stalled = true;
+                        break None;
+                    }
+                }
+            }
+        };
+        let mut activity = self.activity.lock().unwrap();
+        activity.waiting -= 1;
+        if value.is_some() {
+            activity.in_flight -= 1;
+        }
+        value
+    }
+
+    fn write(&mut self, value: i128) {
+        // Sending under the lock keeps in_flight exact while a receiver finishes
+        let mut activity = self.activity.lock().unwrap();
+        for sender in &self.senders {
+            if sender.send(value).is_ok() {
+                activity.in_flight += 1;
+            }
+        }
+        self.outputs.push(value);
+    }
+}
+
+impl MachineIo {
+    // Values left in the channel will never be read
+    fn finish(self) -> Vec<i128> {
+        let mut activity = self.activity.lock().unwrap();
+        activity.in_flight -= self.receiver.try_iter().count();
+        activity.running -= 1;
+        drop(self.receiver);
+        self.outputs
+    }
+}
+
+// Every machine runs on its own thread. Initial inputs (phase settings, the first
+// signal...) are pushed into each IntcodeComputer before running the network.
+// A machine stops when it halts or when every machine that could send it input has
+// finished. If the machines still running all wait for input with nothing in flight,
+// as in a ring nobody started, the network is stuck and run fails with NetworkIdle.
+#[derive(Default)]
+pub struct ThreadedNetwork {
+    computers: Vec<IntcodeComputer>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl ThreadedNetwork {
+    pub fn new() -> ThreadedNetwork {
+        ThreadedNetwork::default()
+    }
+
+    pub fn chain(computers: Vec<IntcodeComputer>) -> ThreadedNetwork {
+        let mut network = ThreadedNetwork::new();
+        for computer in computers {
+            network.add_machine(computer);
+        }
+        for i in 1..network.computers.len() {
+            network.connect(i - 1, i);
+        }
+        network
+    }
+
+    pub fn ring(computers: Vec<IntcodeComputer>) -> ThreadedNetwork {
+        let mut network = ThreadedNetwork::chain(computers);
+        let last = network.computers.len().saturating_sub(1);
+        network.connect(last, 0);
+        network
+    }
+
+    pub fn add_machine(&mut self, computer: IntcodeComputer) -> usize {
+        self.computers.push(computer);
+        self.computers.len() - 1
+    }
+
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.edges.push((from, to));
+    }
+
+    // Returns every value each machine output, indexed like the machines
+    pub fn run(self) -> Result<Vec<Vec<i128>>, IntcodeError> {
+        let (senders, receivers): (Vec<Sender<i128>>, Vec<Receiver<i128>>) =
+            self.computers.iter().map(|_| channel()).unzip();
+        let activity = Arc::new(Mutex::new(Activity {
+            running: self.computers.len(),
+            ..Activity::default()
+        }));
+
+        let edges = self.edges;
+        let handles = self
+            .computers
+            .into_iter()
+            .zip(receivers)
+            .enumerate()
+            .map(|(index, (mut computer, receiver))| {
+                let mut io = MachineIo {
+                    receiver,
+                    senders: edges
+                        .iter()
+                        .filter(|(from, _)| *from == index)
+                        .map(|(_, to)| senders[*to].clone())
+                        .collect(),
+                    outputs: Vec::new(),
+                    activity: Arc::clone(&activity),
+                };
+                thread::spawn(move || {
+                    let result = computer.run_with_io(&mut io);
+                    let outputs = io.finish();
+                    result.map(|_| outputs)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Only the machines may keep channels open, or a starved machine would block forever
+        drop(senders);
+
+        let outputs = handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Intcode machine thread panicked"))
+            .collect::<Result<Vec<Vec<i128>>, IntcodeError>>()?;
+        if activity.lock().unwrap().stalled {
+            return Err(IntcodeError::NetworkIdle);
+        }
+        Ok(outputs)
+    }
+}
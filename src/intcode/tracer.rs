@@ -0,0 +1,96 @@
+use super::{Instruction, Parameter};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    Execute {
+        step: usize,
+        pointer: usize,
+        relative_base: i128,
+        instruction: Instruction,
+    },
+    Fetch {
+        parameter: Parameter,
+        value: i128,
+    },
+    Write {
+        address: usize,
+        old: i128,
+        new: i128,
+    },
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraceEvent::Execute {
+                step,
+                pointer,
+                relative_base,
+                instruction,
+            } => write!(
+                f,
+                "{} ip={} rb={} {}",
+                step, pointer, relative_base, instruction
+            ),
+            TraceEvent::Fetch { parameter, value } => write!(f, "  read {} = {}", parameter, value),
+            TraceEvent::Write { address, old, new } => {
+                write!(f, "  write [{}] {} -> {}", address, old, new)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Tracer {
+    steps: usize,
+    events: Vec<TraceEvent>,
+    file: Option<BufWriter<File>>,
+}
+
+impl Tracer {
+    pub fn new() -> Tracer {
+        Tracer::default()
+    }
+
+    // Events are streamed to the file one per line instead of being kept in memory
+    pub fn to_file(filepath: &str) -> std::io::Result<Tracer> {
+        Ok(Tracer {
+            steps: 0,
+            events: Vec::new(),
+            file: Some(BufWriter::new(File::create(filepath)?)),
+        })
+    }
+
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn record_execute(
+        &mut self,
+        pointer: usize,
+        relative_base: i128,
+        instruction: Instruction,
+    ) {
+        self.steps += 1;
+        self.record(TraceEvent::Execute {
+            step: self.steps,
+            pointer,
+            relative_base,
+            instruction,
+        });
+    }
+
+    pub fn record(&mut self, event: TraceEvent) {
+        match &mut self.file {
+            Some(file) => writeln!(file, "{}", event).expect("Failed to write trace"),
+            None => self.events.push(event),
+        }
+    }
+}
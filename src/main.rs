@@ -8,8 +8,18 @@ mod day4;
 mod day5;
 mod day7;
 mod day8;
+mod intcode;
+mod permutations;
+
+use std::env;
 
 fn main() {
-    day8::star1();
-    day8::star2();
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(|arg| arg.as_str()) {
+        Some("day7") => day7::run_cli(&args[1..]),
+        _ => {
+            day8::star1();
+            day8::star2();
+        }
+    }
 }
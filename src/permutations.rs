@@ -0,0 +1,282 @@
+// Permutation and combination iterators. Every iterator yields its first
+// arrangement too, unlike permutator::Permutation.
+
+// All n! orderings using Heap's algorithm, one swap between consecutive items
+pub struct HeapPermutations<T> {
+    items: Vec<T>,
+    counters: Vec<usize>,
+    index: usize,
+    started: bool,
+}
+
+pub fn heap_permutations<T: Clone>(items: Vec<T>) -> HeapPermutations<T> {
+    HeapPermutations {
+        counters: vec![0; items.len()],
+        items,
+        index: 1,
+        started: false,
+    }
+}
+
+impl<T: Clone> Iterator for HeapPermutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if !self.started {
+            self.started = true;
+            return Some(self.items.clone());
+        }
+        while self.index < self.items.len() {
+            if self.counters[self.index] < self.index {
+                if self.index.is_multiple_of(2) {
+                    self.items.swap(0, self.index);
+                } else {
+                    self.items.swap(self.counters[self.index], self.index);
+                }
+                self.counters[self.index] += 1;
+                self.index = 1;
+                return Some(self.items.clone());
+            }
+            self.counters[self.index] = 0;
+            self.index += 1;
+        }
+        None
+    }
+}
+
+// Distinct orderings in ascending lexicographic order, starting from the sorted items
+pub struct LexicographicPermutations<T> {
+    items: Vec<T>,
+    started: bool,
+    finished: bool,
+}
+
+pub fn lexicographic_permutations<T: Clone + Ord>(
+    mut items: Vec<T>,
+) -> LexicographicPermutations<T> {
+    items.sort();
+    LexicographicPermutations {
+        items,
+        started: false,
+        finished: false,
+    }
+}
+
+impl<T: Clone + Ord> Iterator for LexicographicPermutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.finished {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(self.items.clone());
+        }
+        let items = &mut self.items;
+        let pivot = match (1..items.len()).rev().find(|&i| items[i - 1] < items[i]) {
+            Some(i) => i - 1,
+            None => {
+                self.finished = true;
+                return None;
+            }
+        };
+        let successor = (pivot + 1..items.len())
+            .rev()
+            .find(|&i| items[pivot] < items[i])
+            .unwrap();
+        items.swap(pivot, successor);
+        items[pivot + 1..].reverse();
+        Some(items.clone())
+    }
+}
+
+// Ordered selections of k items, n!/(n-k)! of them, in lexicographic order of positions
+pub struct KPermutations<T> {
+    items: Vec<T>,
+    k: usize,
+    indices: Vec<usize>,
+    cycles: Vec<usize>,
+    started: bool,
+    finished: bool,
+}
+
+pub fn k_permutations<T: Clone>(items: Vec<T>, k: usize) -> KPermutations<T> {
+    let n = items.len();
+    KPermutations {
+        indices: (0..n).collect(),
+        cycles: (0..k.min(n)).map(|i| n - i).collect(),
+        finished: k > n,
+        items,
+        k,
+        started: false,
+    }
+}
+
+impl<T: Clone> KPermutations<T> {
+    fn current(&self) -> Vec<T> {
+        self.indices[..self.k]
+            .iter()
+            .map(|i| self.items[*i].clone())
+            .collect()
+    }
+}
+
+impl<T: Clone> Iterator for KPermutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.finished {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(self.current());
+        }
+        let n = self.items.len();
+        for i in (0..self.k).rev() {
+            self.cycles[i] -= 1;
+            if self.cycles[i] == 0 {
+                self.indices[i..].rotate_left(1);
+                self.cycles[i] = n - i;
+            } else {
+                let j = n - self.cycles[i];
+                self.indices.swap(i, j);
+                return Some(self.current());
+            }
+        }
+        self.finished = true;
+        None
+    }
+}
+
+// Unordered selections of k items, keeping the original relative order
+pub struct Combinations<T> {
+    items: Vec<T>,
+    indices: Vec<usize>,
+    started: bool,
+    finished: bool,
+}
+
+pub fn combinations<T: Clone>(items: Vec<T>, k: usize) -> Combinations<T> {
+    Combinations {
+        finished: k > items.len(),
+        items,
+        indices: (0..k).collect(),
+        started: false,
+    }
+}
+
+impl<T: Clone> Combinations<T> {
+    fn current(&self) -> Vec<T> {
+        self.indices
+            .iter()
+            .map(|i| self.items[*i].clone())
+            .collect()
+    }
+}
+
+impl<T: Clone> Iterator for Combinations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.finished {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(self.current());
+        }
+        let n = self.items.len();
+        let k = self.indices.len();
+        let position = match (0..k).rev().find(|&i| self.indices[i] != i + n - k) {
+            Some(position) => position,
+            None => {
+                self.finished = true;
+                return None;
+            }
+        };
+        self.indices[position] += 1;
+        for i in position + 1..k {
+            self.indices[i] = self.indices[i - 1] + 1;
+        }
+        Some(self.current())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn factorial(n: usize) -> usize {
+        (1..=n).product()
+    }
+
+    fn assert_distinct(items: &[Vec<i32>], expected: usize) {
+        let distinct: HashSet<&Vec<i32>> = items.iter().collect();
+        assert_eq!(items.len(), expected);
+        assert_eq!(distinct.len(), expected);
+    }
+
+    #[test]
+    fn heap_yields_every_permutation_once() {
+        for n in 0..7 {
+            let items: Vec<Vec<i32>> = heap_permutations((0..n as i32).collect()).collect();
+            assert_distinct(&items, factorial(n));
+        }
+    }
+
+    #[test]
+    fn heap_starts_with_the_initial_order() {
+        assert_eq!(heap_permutations(vec![3, 1, 2]).next(), Some(vec![3, 1, 2]));
+    }
+
+    #[test]
+    fn lexicographic_yields_every_permutation_once_in_order() {
+        for n in 0..7 {
+            let items: Vec<Vec<i32>> =
+                lexicographic_permutations((0..n as i32).rev().collect()).collect();
+            assert_distinct(&items, factorial(n));
+            assert!(items.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn lexicographic_skips_repeated_orderings() {
+        let items: Vec<Vec<i32>> = lexicographic_permutations(vec![1, 1, 2]).collect();
+        assert_eq!(items, vec![vec![1, 1, 2], vec![1, 2, 1], vec![2, 1, 1]]);
+    }
+
+    #[test]
+    fn k_permutations_yield_every_selection_once() {
+        for n in 0..6 {
+            for k in 0..=n {
+                let items: Vec<Vec<i32>> = k_permutations((0..n as i32).collect(), k).collect();
+                assert_distinct(&items, factorial(n) / factorial(n - k));
+                assert!(items.iter().all(|item| item.len() == k));
+            }
+        }
+    }
+
+    #[test]
+    fn k_permutations_with_k_above_n_are_empty() {
+        assert_eq!(k_permutations(vec![1, 2], 3).count(), 0);
+        assert_eq!(k_permutations(Vec::<i32>::new(), 1).count(), 0);
+    }
+
+    #[test]
+    fn combinations_count() {
+        for n in 0..7 {
+            for k in 0..=n {
+                let items: Vec<Vec<i32>> = combinations((0..n as i32).collect(), k).collect();
+                let expected = factorial(n) / (factorial(k) * factorial(n - k));
+                assert_distinct(&items, expected);
+                assert!(items
+                    .iter()
+                    .all(|item| item.windows(2).all(|p| p[0] < p[1])));
+            }
+        }
+        assert_eq!(combinations(vec![1, 2], 3).count(), 0);
+    }
+}